@@ -0,0 +1,182 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::config::map_chunks;
+use crate::Error;
+
+/// A token to cooperatively cancel a computation started with [`compute_cancellable`].
+///
+/// Clones of a token share the same state, so one clone can be kept to cancel the computation from another thread
+/// while the computation checks another one.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::{compute_cancellable, CancellationToken, Error};
+/// let token = CancellationToken::new();
+/// let canceller = token.clone();
+/// let output = compute_cancellable(0..100, move |t| {
+///     if t == 10 {
+///         canceller.cancel();
+///     }
+///     t * 2
+/// }, &token);
+/// assert!(matches!(output, Err(Error::Cancelled)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the computations checking this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Computes the given function `f` on each element of `input` in parallel, like [`compute`](crate::compute), until
+/// `token` is cancelled.
+///
+/// The threads check the token before computing each element, and stop as soon as it is cancelled: the call then
+/// returns once the elements being computed at that time are done, without waiting for the rest of the input.
+///
+/// # Errors
+///
+/// Returns [`Error::Cancelled`] if the token was cancelled before the computation finished, even if the threads were
+/// already past their last check and computed all the elements.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::{compute_cancellable, CancellationToken};
+/// let token = CancellationToken::new();
+/// assert_eq!(compute_cancellable(0..5, |t| t * 2, &token).unwrap(), vec![0, 2, 4, 6, 8]);
+/// ```
+pub fn compute_cancellable<T, R, F>(
+    input: impl IntoIterator<Item = T>,
+    f: F,
+    token: &CancellationToken,
+) -> Result<Vec<R>, Error>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let checked = token.clone();
+
+    let outputs = map_chunks(input, move |_, chunk| {
+        let mut output = Vec::with_capacity(chunk.len());
+
+        for item in chunk {
+            if checked.is_cancelled() {
+                return None;
+            }
+
+            output.push(f(item));
+        }
+
+        Some(output)
+    });
+
+    // Whether the threads noticed it or not, the caller asked for the computation to stop
+    if token.is_cancelled() {
+        return Err(Error::Cancelled);
+    }
+
+    Ok(outputs
+        .into_iter()
+        .flat_map(|output| output.expect("a chunk is only interrupted once the token is cancelled"))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::{compute_cancellable, CancellationToken, Error};
+
+    #[test]
+    fn test_compute_cancellable_not_cancelled() {
+        let token = CancellationToken::new();
+        let result = compute_cancellable(0..1000, |x| x * 2, &token);
+        assert_eq!(
+            result.unwrap(),
+            (0..1000).map(|x| x * 2).collect::<Vec<_>>()
+        );
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn test_compute_cancellable_already_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = compute_cancellable(0..1000, |x| x * 2, &token);
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_compute_cancellable_stops_promptly() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let gate = token.clone();
+        let processed = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&processed);
+
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+
+        // Every element is blocked until the token is cancelled, so each thread computes at most one of them
+        let start = Instant::now();
+        let result = compute_cancellable(
+            0..100_000,
+            move |x| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                while !gate.is_cancelled() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                x
+            },
+            &token,
+        );
+        handle.join().unwrap();
+
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(processed.load(Ordering::SeqCst) < 100_000);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_compute_cancellable_cancelled_after_last_check() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+
+        // The last element cancels the token once every check is done, which still cancels the computation
+        let result = compute_cancellable(
+            0..10,
+            move |x| {
+                if x == 9 {
+                    canceller.cancel();
+                }
+                x
+            },
+            &token,
+        );
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+}
@@ -0,0 +1,666 @@
+use std::env;
+use std::io;
+use std::num::NonZeroUsize;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, available_parallelism};
+
+use crate::pool::{is_worker_thread, TaskHandle};
+use crate::{split_into_chunks, ComputeInput, Error, Split, ThreadPool, THRESHOLD};
+
+/// A builder to configure how a computation is split into chunks and executed.
+///
+/// Every setting is optional: `Compute::new().run(input, f)` behaves exactly like [`compute`](crate::compute).
+/// The configuration is validated when the computation is run.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::Compute;
+/// let output = Compute::new()
+///     .threads(2)
+///     .sequential_threshold(100)
+///     .chunk_size(16)
+///     .run(0..1000, |t| t * 2)
+///     .unwrap();
+/// assert_eq!(output, (0..1000).map(|t| t * 2).collect::<Vec<_>>());
+/// ```
+#[derive(Clone, Copy)]
+pub struct Compute<'p> {
+    threads: Option<usize>,
+    sequential_threshold: usize,
+    chunk_size: Option<usize>,
+    pool: Option<&'p ThreadPool>,
+    fallback: Fallback,
+    schedule: Schedule,
+}
+
+/// The environment variable overriding the default number of threads.
+///
+/// When it is set to a positive integer, it is used instead of the number of available CPUs, both for the
+/// [global](ThreadPool::global) pool and for computations that don't set [`Compute::threads`].
+pub const THREADS_ENV_VAR: &str = "SIMPLE_PARALLEL_COMPUTE_THREADS";
+
+/// What a computation does when the number of available CPUs cannot be determined, which happens in some sandboxed
+/// environments.
+///
+/// The fallback is only used when neither [`Compute::threads`] nor the [`THREADS_ENV_VAR`] environment variable set
+/// the number of threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Fallback {
+    /// Compute the input in the calling thread.
+    #[default]
+    Sequential,
+    /// Split the input between the given number of threads.
+    Threads(usize),
+    /// Fail with [`Error::ParallelismUnavailable`].
+    Error,
+}
+
+/// How the chunks of the input are distributed between the threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Schedule {
+    /// Every thread is assigned the same number of chunks upfront.
+    ///
+    /// This has the lowest overhead, and works best when every element takes about the same time to compute.
+    #[default]
+    Static,
+    /// Every thread claims the next unprocessed chunk as soon as it is done with the previous one.
+    ///
+    /// The input is split into smaller chunks by default, so that threads that got cheap elements keep working while
+    /// others compute expensive ones. This works best when the cost of the elements is uneven.
+    Dynamic,
+}
+
+/// The number of chunks per thread the input is split into by default with [`Schedule::Dynamic`].
+const DYNAMIC_CHUNKS_PER_THREAD: usize = 8;
+
+/// How an input of a given size is split between the threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Plan {
+    pub(crate) threads: usize,
+    pub(crate) chunk_size: usize,
+    pub(crate) schedule: Schedule,
+}
+
+impl Default for Compute<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'p> Compute<'p> {
+    /// Creates a configuration with the default settings.
+    pub fn new() -> Self {
+        Self {
+            threads: None,
+            sequential_threshold: THRESHOLD,
+            chunk_size: None,
+            pool: None,
+            fallback: Fallback::Sequential,
+            schedule: Schedule::Static,
+        }
+    }
+
+    /// Sets the number of threads working on the computation at the same time.
+    ///
+    /// Defaults to the number of threads of the pool. The number of threads actually running is also limited by the
+    /// size of the pool.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Sets the input size under which the computation is performed in the calling thread.
+    ///
+    /// Defaults to the `THRESHOLD` constant. A threshold of zero always dispatches the computation onto the pool.
+    pub fn sequential_threshold(mut self, sequential_threshold: usize) -> Self {
+        self.sequential_threshold = sequential_threshold;
+        self
+    }
+
+    /// Sets the number of elements in each chunk of the input.
+    ///
+    /// Defaults to an even split of the input between the threads, or to smaller chunks with [`Schedule::Dynamic`].
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Sets how the chunks are distributed between the threads.
+    ///
+    /// Defaults to [`Schedule::Static`]. The output is in input order with every schedule.
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Sets the pool the computation is dispatched onto.
+    ///
+    /// Defaults to the [global](ThreadPool::global) pool.
+    pub fn pool(mut self, pool: &'p ThreadPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Sets what to do when the number of available CPUs cannot be determined.
+    ///
+    /// Defaults to [`Fallback::Sequential`].
+    pub fn fallback(mut self, fallback: Fallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Computes the given function `f` on each element of `input` with this configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration is invalid, for example if it has zero threads, or if the number of
+    /// threads cannot be determined with the [`Fallback::Error`] policy.
+    pub fn run<T, R, F>(&self, input: impl ComputeInput<Item = T>, f: F) -> Result<Vec<R>, Error>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        let outputs = self.map_split(input.into_input(), move |_, chunk| {
+            chunk.into_iter().map(&f).collect::<Vec<_>>()
+        })?;
+
+        Ok(outputs.into_iter().flatten().collect())
+    }
+
+    /// Returns how an input of `input_size` elements is split, or `None` if it should be computed sequentially.
+    pub(crate) fn plan(&self, input_size: usize) -> Result<Option<Plan>, Error> {
+        if self.threads == Some(0) || self.fallback == Fallback::Threads(0) {
+            return Err(Error::ZeroThreads);
+        }
+
+        if self.chunk_size == Some(0) {
+            return Err(Error::ZeroChunkSize);
+        }
+
+        if input_size == 0 || input_size < self.sequential_threshold {
+            return Ok(None);
+        }
+
+        let threads = match self.threads.or(self.pool.map(ThreadPool::threads)) {
+            Some(threads) => threads,
+            None => match self.apply_fallback(default_threads())? {
+                Some(threads) => threads,
+                None => return Ok(None),
+            },
+        };
+
+        // The chunk size is calculated that way because we want to ensure that each chunk has roughly the same number
+        // of elements, and that all elements are distributed evenly among the threads.
+        let chunks_count = match self.schedule {
+            Schedule::Static => threads,
+            Schedule::Dynamic => threads * DYNAMIC_CHUNKS_PER_THREAD,
+        };
+        let chunk_size = self
+            .chunk_size
+            .unwrap_or_else(|| input_size.div_ceil(chunks_count));
+
+        Ok(Some(Plan {
+            threads,
+            chunk_size,
+            schedule: self.schedule,
+        }))
+    }
+
+    /// Applies the fallback policy if the default number of threads is unknown, returning `None` to compute
+    /// sequentially.
+    fn apply_fallback(
+        &self,
+        default_threads: Result<usize, io::ErrorKind>,
+    ) -> Result<Option<usize>, Error> {
+        match (default_threads, self.fallback) {
+            (Ok(threads), _) => Ok(Some(threads)),
+            (Err(_), Fallback::Sequential) => Ok(None),
+            (Err(_), Fallback::Threads(threads)) => Ok(Some(threads)),
+            (Err(kind), Fallback::Error) => {
+                Err(Error::ParallelismUnavailable(io::Error::from(kind)))
+            }
+        }
+    }
+
+    /// Splits `input` into chunks and runs `job` on each of them in parallel, returning the outputs in chunk order.
+    ///
+    /// `job` receives the index of the first element of the chunk in `input`, and the chunk itself. A panic in `job`
+    /// is propagated to the caller.
+    pub(crate) fn map_chunks<T, X, J>(
+        &self,
+        input: impl IntoIterator<Item = T>,
+        job: J,
+    ) -> Result<Vec<X>, Error>
+    where
+        T: Send + 'static,
+        X: Send + 'static,
+        J: Fn(usize, Vec<T>) -> X + Send + Sync + 'static,
+    {
+        // Collecting a vector into a vector reuses its allocation, so vectors are never copied here
+        let input: Vec<T> = input.into_iter().collect();
+
+        self.map_split(input, job)
+    }
+
+    /// Splits `input` into chunks and runs `job` on each of them in parallel, like [`Compute::map_chunks`], for any
+    /// input that can be split.
+    pub(crate) fn map_split<S, X, J>(&self, input: S, job: J) -> Result<Vec<X>, Error>
+    where
+        S: Split + Send + 'static,
+        X: Send + 'static,
+        J: Fn(usize, S) -> X + Send + Sync + 'static,
+    {
+        Ok(self.spawn_split(input, job)?.join())
+    }
+
+    /// Splits `input` into chunks and dispatches `job` on each of them onto the pool, without waiting for the outputs.
+    ///
+    /// Small inputs are still computed in the calling thread before returning.
+    pub(crate) fn spawn_split<S, X, J>(&self, input: S, job: J) -> Result<Pending<X>, Error>
+    where
+        S: Split + Send + 'static,
+        X: Send + 'static,
+        J: Fn(usize, S) -> X + Send + Sync + 'static,
+    {
+        let plan = self.plan(input.len())?;
+
+        // Computations started from a pool job run in the current thread: waiting for other jobs from inside a worker
+        // could leave the pool with no thread to run them.
+        let Some(plan) = plan.filter(|_| !is_worker_thread()) else {
+            return Ok(Pending::Done(vec![job(0, input)]));
+        };
+
+        Ok(self.dispatch(plan, input, job))
+    }
+
+    /// Splits `input` into chunks according to `plan` and dispatches `job` on each of them onto the pool.
+    fn dispatch<S, X, J>(&self, plan: Plan, input: S, job: J) -> Pending<X>
+    where
+        S: Split + Send + 'static,
+        X: Send + 'static,
+        J: Fn(usize, S) -> X + Send + Sync + 'static,
+    {
+        let chunks = split_into_chunks(input, plan.chunk_size);
+        let chunks_count = chunks.len();
+        let threads_count = plan.threads.min(chunks_count);
+
+        let job = Arc::new(job);
+        let pool = self.pool.unwrap_or_else(|| ThreadPool::global());
+
+        let task_handles = match plan.schedule {
+            Schedule::Static => {
+                // Chunk `i` goes to thread `i % threads_count`, so that every thread gets an even share of the chunks.
+                let mut assignments: Vec<Vec<_>> = (0..threads_count).map(|_| Vec::new()).collect();
+                for (index, chunk) in chunks.into_iter().enumerate() {
+                    assignments[index % threads_count].push((index, chunk));
+                }
+
+                assignments
+                    .into_iter()
+                    .map(|assigned| {
+                        let job = Arc::clone(&job);
+
+                        pool.spawn(move || {
+                            assigned
+                                .into_iter()
+                                .map(|(index, chunk)| (index, job(index * plan.chunk_size, chunk)))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            }
+            Schedule::Dynamic => {
+                let queue = Arc::new(Mutex::new(chunks.into_iter().enumerate()));
+
+                (0..threads_count)
+                    .map(|_| {
+                        let job = Arc::clone(&job);
+                        let queue = Arc::clone(&queue);
+
+                        pool.spawn(move || {
+                            let mut outputs = Vec::new();
+
+                            loop {
+                                // The queue is only locked while claiming a chunk, not while computing it
+                                let next = queue.lock().unwrap().next();
+                                let Some((index, chunk)) = next else {
+                                    break;
+                                };
+
+                                outputs.push((index, job(index * plan.chunk_size, chunk)));
+                            }
+
+                            outputs
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        Pending::Running {
+            chunks_count,
+            task_handles,
+        }
+    }
+}
+
+/// The outputs of the chunks of a computation dispatched with [`Compute::spawn_split`].
+pub(crate) enum Pending<X> {
+    /// The computation was performed in the calling thread.
+    Done(Vec<X>),
+    /// The computation is running on the pool, every task computing some of the chunks.
+    Running {
+        chunks_count: usize,
+        task_handles: Vec<TaskHandle<Vec<(usize, X)>>>,
+    },
+}
+
+impl<X> Pending<X> {
+    /// Waits for all the chunks to be computed, returning their outputs in chunk order.
+    ///
+    /// A panic while computing a chunk is propagated to the caller.
+    pub(crate) fn join(self) -> Vec<X> {
+        let (chunks_count, task_handles) = match self {
+            Pending::Done(outputs) => return outputs,
+            Pending::Running {
+                chunks_count,
+                task_handles,
+            } => (chunks_count, task_handles),
+        };
+
+        let mut outputs: Vec<Option<X>> = (0..chunks_count).map(|_| None).collect();
+        for handle in task_handles {
+            for (index, output) in handle
+                .join()
+                .unwrap_or_else(|payload| resume_unwind(payload))
+            {
+                outputs[index] = Some(output);
+            }
+        }
+
+        outputs
+            .into_iter()
+            .map(|output| output.expect("every chunk is computed"))
+            .collect()
+    }
+}
+
+/// Returns the default number of threads, read from the [`THREADS_ENV_VAR`] environment variable or from the number
+/// of available CPUs.
+///
+/// The value is determined once and cached, as querying the available CPUs is relatively expensive.
+pub(crate) fn default_threads() -> Result<usize, io::ErrorKind> {
+    static DEFAULT_THREADS: OnceLock<Result<usize, io::ErrorKind>> = OnceLock::new();
+
+    *DEFAULT_THREADS.get_or_init(|| {
+        match parse_threads(env::var(THREADS_ENV_VAR).ok().as_deref()) {
+            Some(threads) => Ok(threads),
+            None => available_parallelism()
+                .map(NonZeroUsize::get)
+                .map_err(|error| error.kind()),
+        }
+    })
+}
+
+/// Parses a number of threads from the value of the [`THREADS_ENV_VAR`] environment variable, ignoring invalid values.
+fn parse_threads(value: Option<&str>) -> Option<usize> {
+    value?.trim().parse().ok().filter(|&threads| threads > 0)
+}
+
+/// Runs [`Compute::map_chunks`] with the default configuration, which is always valid.
+pub(crate) fn map_chunks<T, X, J>(input: impl IntoIterator<Item = T>, job: J) -> Vec<X>
+where
+    T: Send + 'static,
+    X: Send + 'static,
+    J: Fn(usize, Vec<T>) -> X + Send + Sync + 'static,
+{
+    Compute::new()
+        .map_chunks(input, job)
+        .expect("the default configuration is valid")
+}
+
+/// Computes `f` on each element of `input` in the background, sending every result with the index of its element over
+/// the returned channel as soon as it is computed.
+///
+/// The computation is dispatched onto the pool, even for small inputs, so `f` never runs in the calling thread. When
+/// called from a pool job, the elements are computed one after the other in a dedicated thread instead, so that the
+/// pool isn't waited for from one of its own threads. The threads stop computing the remaining elements once the
+/// receiver is dropped or `stop` returns `true`. A panic in `f` stops the thread computing the element, and its payload
+/// is sent in place of the result.
+pub(crate) fn spawn_each<T, R, F, S>(
+    input: Vec<T>,
+    f: F,
+    stop: S,
+) -> Receiver<thread::Result<(usize, R)>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+    S: Fn() -> bool + Send + Sync + 'static,
+{
+    let (sender, receiver) = channel();
+
+    // Small chunks are claimed dynamically, so the results come roughly in input order
+    let compute = Compute::new()
+        .sequential_threshold(0)
+        .schedule(Schedule::Dynamic);
+    let plan = compute
+        .plan(input.len())
+        .expect("the default configuration is valid");
+
+    let job = move |offset, chunk: Vec<T>| {
+        let outcome = catch_unwind(AssertUnwindSafe(|| {
+            for (index, item) in chunk.into_iter().enumerate() {
+                if stop() || sender.send(Ok((offset + index, f(item)))).is_err() {
+                    break;
+                }
+            }
+        }));
+
+        if let Err(payload) = outcome {
+            let _ = sender.send(Err(payload));
+        }
+    };
+
+    // The results are sent as soon as they are computed, so the computation is never joined
+    match plan.filter(|_| !is_worker_thread()) {
+        Some(plan) => drop(compute.dispatch(plan, input, job)),
+        None => drop(thread::spawn(move || job(0, input))),
+    }
+
+    receiver
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::config::{parse_threads, Plan};
+    use crate::{Compute, Error, Fallback, Schedule, ThreadPool};
+
+    #[test]
+    fn test_compute_builder_default() {
+        let result = Compute::new().run(0..100, |x| x * 2).unwrap();
+        assert_eq!(result, (0..100).map(|x| x * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_compute_builder_custom_settings() {
+        let pool = ThreadPool::new(3);
+
+        for threads in 1..6 {
+            for chunk_size in 1..12 {
+                let result = Compute::new()
+                    .threads(threads)
+                    .sequential_threshold(0)
+                    .chunk_size(chunk_size)
+                    .pool(&pool)
+                    .run(0..50, |x| x + 1)
+                    .unwrap();
+                assert_eq!(result, (1..51).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_builder_sequential_threshold() {
+        let caller = std::thread::current().id();
+        let result = Compute::new()
+            .sequential_threshold(1000)
+            .run(0..100, move |_| std::thread::current().id() == caller)
+            .unwrap();
+        assert!(result.into_iter().all(|on_caller| on_caller));
+    }
+
+    #[test]
+    fn test_compute_builder_plan() {
+        let compute = Compute::new().threads(4);
+        assert_eq!(compute.plan(0).unwrap(), None);
+        assert_eq!(compute.plan(4).unwrap(), None);
+        assert_eq!(
+            compute.plan(10).unwrap(),
+            Some(Plan {
+                threads: 4,
+                chunk_size: 3,
+                schedule: Schedule::Static,
+            })
+        );
+        assert_eq!(
+            compute.chunk_size(2).plan(10).unwrap(),
+            Some(Plan {
+                threads: 4,
+                chunk_size: 2,
+                schedule: Schedule::Static,
+            })
+        );
+    }
+
+    #[test]
+    fn test_compute_builder_zero_threads() {
+        let result = Compute::new().threads(0).run(vec![1, 2, 3], |x| x);
+        assert!(matches!(result, Err(Error::ZeroThreads)));
+    }
+
+    #[test]
+    fn test_compute_builder_zero_chunk_size() {
+        let result = Compute::new().chunk_size(0).run(vec![1, 2, 3], |x| x);
+        assert!(matches!(result, Err(Error::ZeroChunkSize)));
+    }
+
+    #[test]
+    fn test_parse_threads() {
+        assert_eq!(parse_threads(None), None);
+        assert_eq!(parse_threads(Some("")), None);
+        assert_eq!(parse_threads(Some("0")), None);
+        assert_eq!(parse_threads(Some("-2")), None);
+        assert_eq!(parse_threads(Some("many")), None);
+        assert_eq!(parse_threads(Some("8")), Some(8));
+        assert_eq!(parse_threads(Some(" 3\n")), Some(3));
+    }
+
+    #[test]
+    fn test_compute_builder_fallback() {
+        let unavailable = Err(io::ErrorKind::Unsupported);
+
+        assert_eq!(Compute::new().apply_fallback(Ok(4)).unwrap(), Some(4));
+        assert_eq!(Compute::new().apply_fallback(unavailable).unwrap(), None);
+        assert_eq!(
+            Compute::new()
+                .fallback(Fallback::Threads(2))
+                .apply_fallback(unavailable)
+                .unwrap(),
+            Some(2)
+        );
+        assert!(matches!(
+            Compute::new().fallback(Fallback::Error).apply_fallback(unavailable),
+            Err(Error::ParallelismUnavailable(error)) if error.kind() == io::ErrorKind::Unsupported
+        ));
+    }
+
+    #[test]
+    fn test_compute_builder_zero_fallback_threads() {
+        let result = Compute::new()
+            .fallback(Fallback::Threads(0))
+            .run(vec![1, 2, 3], |x| x);
+        assert!(matches!(result, Err(Error::ZeroThreads)));
+    }
+
+    #[test]
+    fn test_compute_builder_dynamic_plan() {
+        let compute = Compute::new().threads(4).schedule(Schedule::Dynamic);
+        assert_eq!(
+            compute.plan(100).unwrap(),
+            Some(Plan {
+                threads: 4,
+                chunk_size: 4,
+                schedule: Schedule::Dynamic
+            })
+        );
+        assert_eq!(
+            compute.chunk_size(10).plan(100).unwrap(),
+            Some(Plan {
+                threads: 4,
+                chunk_size: 10,
+                schedule: Schedule::Dynamic
+            })
+        );
+    }
+
+    #[test]
+    fn test_compute_builder_dynamic_schedule() {
+        let pool = ThreadPool::new(3);
+
+        for threads in 1..6 {
+            for chunk_size in 1..12 {
+                let result = Compute::new()
+                    .threads(threads)
+                    .sequential_threshold(0)
+                    .chunk_size(chunk_size)
+                    .schedule(Schedule::Dynamic)
+                    .pool(&pool)
+                    .run(0..50, |x| x + 1)
+                    .unwrap();
+                assert_eq!(result, (1..51).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_builder_dynamic_uneven_cost() {
+        let pool = ThreadPool::new(4);
+
+        // Returns the threads which computed the slow elements, all at the start of the input
+        let slow_threads = |schedule| {
+            let threads = Arc::new(Mutex::new(HashSet::new()));
+            let recorded = Arc::clone(&threads);
+
+            let result = Compute::new()
+                .schedule(schedule)
+                .pool(&pool)
+                .run(0..64u64, move |x| {
+                    if x < 8 {
+                        recorded.lock().unwrap().insert(thread::current().id());
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                    x * 2
+                })
+                .unwrap();
+            assert_eq!(result, (0..64).map(|x| x * 2).collect::<Vec<_>>());
+
+            let threads = threads.lock().unwrap().len();
+            threads
+        };
+
+        // The slow elements all fall in the first static chunk, but are claimed by several threads in small chunks
+        assert_eq!(slow_threads(Schedule::Static), 1);
+        assert!(slow_threads(Schedule::Dynamic) > 1);
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
+/// An error returned when a computation cannot be run with the requested configuration.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The computation was configured to run on zero threads.
+    ZeroThreads,
+    /// The computation was configured to split its input into chunks of zero elements.
+    ZeroChunkSize,
+    /// The number of available CPUs cannot be determined, and the [`Fallback::Error`](crate::Fallback::Error) policy
+    /// was selected.
+    ParallelismUnavailable(io::Error),
+    /// Inputs that must be processed together don't have the same number of elements.
+    LengthMismatch {
+        /// The number of elements of the first input.
+        left: usize,
+        /// The number of elements of the second input.
+        right: usize,
+    },
+    /// The computation was cancelled with its [`CancellationToken`](crate::CancellationToken).
+    Cancelled,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ZeroThreads => write!(f, "the number of threads must be greater than zero"),
+            Error::ZeroChunkSize => write!(f, "the chunk size must be greater than zero"),
+            Error::ParallelismUnavailable(error) => write!(f, "cannot get parallelism: {error}"),
+            Error::LengthMismatch { left, right } => {
+                write!(f, "the inputs have different lengths: {left} and {right}")
+            }
+            Error::Cancelled => write!(f, "the computation was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ParallelismUnavailable(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use std::ops::Range;
+
+use crate::slice::map_split_scoped;
+use crate::{compute_slice, ComputeInput};
+
+/// Extension methods to run parallel computations on vectors, slices and ranges as chained method calls.
+///
+/// Vectors are consumed and their elements are passed by value, like with [`compute`](crate::compute). Ranges of
+/// integers are split into smaller ranges, so their elements are passed by value without ever being collected into a
+/// vector. Slices (and references to vectors) are borrowed and their elements are passed by reference, like with
+/// [`compute_slice`](crate::compute_slice).
+///
+/// The methods run on scoped threads, like [`compute_slice`](crate::compute_slice), so the functions only need to be
+/// `Sync` and may borrow local variables. Use [`compute`](crate::compute) and the other functions of the crate to run
+/// on the [`ThreadPool`](crate::ThreadPool) instead.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::ParallelComputeExt;
+/// let squares = (1..11u64).par_map(|t| t * t);
+/// let even_squares = squares.as_slice().par_filter(|t| **t % 2 == 0);
+/// assert_eq!(even_squares, vec![&4, &16, &36, &64, &100]);
+///
+/// let sum = squares.as_slice().par_reduce(|t| *t, || 0, |a, b| a + b);
+/// assert_eq!(sum, 385);
+/// ```
+pub trait ParallelComputeExt<'a>: Sized {
+    /// The type of the elements passed to the functions.
+    type Item: 'a;
+
+    /// Computes `f` on each element in parallel, returning the results in order.
+    fn par_map<R, F>(self, f: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(Self::Item) -> R + Sync;
+
+    /// Returns the elements matching `predicate`, evaluated in parallel, in order.
+    fn par_filter<P>(self, predicate: P) -> Vec<Self::Item>
+    where
+        P: Fn(&Self::Item) -> bool + Sync;
+
+    /// Calls `f` on each element in parallel, returning once all the calls are done.
+    fn par_for_each<F>(self, f: F)
+    where
+        F: Fn(Self::Item) + Sync;
+
+    /// Computes `map` on each element in parallel, and reduces the results with the associative `combine` function,
+    /// of which `identity()` is a neutral element, like [`compute_reduce`](crate::compute_reduce).
+    fn par_reduce<R, M, I, C>(self, map: M, identity: I, combine: C) -> R
+    where
+        R: Send,
+        M: Fn(Self::Item) -> R + Sync,
+        I: Fn() -> R + Sync,
+        C: Fn(R, R) -> R + Sync;
+}
+
+impl<'a, T> ParallelComputeExt<'a> for Vec<T>
+where
+    T: Send + 'a,
+{
+    type Item = T;
+
+    fn par_map<R, F>(self, f: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(T) -> R + Sync,
+    {
+        map_split_scoped(self, |chunk| chunk.into_iter().map(&f).collect::<Vec<_>>())
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn par_filter<P>(self, predicate: P) -> Vec<T>
+    where
+        P: Fn(&T) -> bool + Sync,
+    {
+        map_split_scoped(self, |chunk| {
+            chunk.into_iter().filter(&predicate).collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn par_for_each<F>(self, f: F)
+    where
+        F: Fn(T) + Sync,
+    {
+        map_split_scoped(self, |chunk| chunk.into_iter().for_each(&f));
+    }
+
+    fn par_reduce<R, M, I, C>(self, map: M, identity: I, combine: C) -> R
+    where
+        R: Send,
+        M: Fn(T) -> R + Sync,
+        I: Fn() -> R + Sync,
+        C: Fn(R, R) -> R + Sync,
+    {
+        map_split_scoped(self, |chunk| {
+            chunk.into_iter().map(&map).fold(identity(), &combine)
+        })
+        .into_iter()
+        .fold(identity(), &combine)
+    }
+}
+
+/// Ranges of integers are split into smaller ranges, so their elements are never collected into a vector.
+macro_rules! impl_parallel_compute_ext_for_ranges {
+    ($($t:ty),*) => {
+        $(
+            impl<'a> ParallelComputeExt<'a> for Range<$t> {
+                type Item = $t;
+
+                fn par_map<R, F>(self, f: F) -> Vec<R>
+                where
+                    R: Send,
+                    F: Fn($t) -> R + Sync,
+                {
+                    map_split_scoped(self.into_input(), |chunk| {
+                        chunk.into_iter().map(&f).collect::<Vec<_>>()
+                    })
+                    .into_iter()
+                    .flatten()
+                    .collect()
+                }
+
+                fn par_filter<P>(self, predicate: P) -> Vec<$t>
+                where
+                    P: Fn(&$t) -> bool + Sync,
+                {
+                    map_split_scoped(self.into_input(), |chunk| {
+                        chunk.into_iter().filter(&predicate).collect::<Vec<_>>()
+                    })
+                    .into_iter()
+                    .flatten()
+                    .collect()
+                }
+
+                fn par_for_each<F>(self, f: F)
+                where
+                    F: Fn($t) + Sync,
+                {
+                    map_split_scoped(self.into_input(), |chunk| chunk.into_iter().for_each(&f));
+                }
+
+                fn par_reduce<R, M, I, C>(self, map: M, identity: I, combine: C) -> R
+                where
+                    R: Send,
+                    M: Fn($t) -> R + Sync,
+                    I: Fn() -> R + Sync,
+                    C: Fn(R, R) -> R + Sync,
+                {
+                    map_split_scoped(self.into_input(), |chunk| {
+                        chunk.into_iter().map(&map).fold(identity(), &combine)
+                    })
+                    .into_iter()
+                    .fold(identity(), &combine)
+                }
+            }
+        )*
+    };
+}
+
+impl_parallel_compute_ext_for_ranges!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl<'a, T> ParallelComputeExt<'a> for &'a [T]
+where
+    T: Sync,
+{
+    type Item = &'a T;
+
+    fn par_map<R, F>(self, f: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(&'a T) -> R + Sync,
+    {
+        compute_slice(self, f)
+    }
+
+    fn par_filter<P>(self, predicate: P) -> Vec<&'a T>
+    where
+        P: Fn(&&'a T) -> bool + Sync,
+    {
+        map_split_scoped(self, |chunk: &'a [T]| {
+            chunk.iter().filter(&predicate).collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn par_for_each<F>(self, f: F)
+    where
+        F: Fn(&'a T) + Sync,
+    {
+        map_split_scoped(self, |chunk: &'a [T]| chunk.iter().for_each(&f));
+    }
+
+    fn par_reduce<R, M, I, C>(self, map: M, identity: I, combine: C) -> R
+    where
+        R: Send,
+        M: Fn(&'a T) -> R + Sync,
+        I: Fn() -> R + Sync,
+        C: Fn(R, R) -> R + Sync,
+    {
+        map_split_scoped(self, |chunk: &'a [T]| {
+            chunk.iter().map(&map).fold(identity(), &combine)
+        })
+        .into_iter()
+        .fold(identity(), &combine)
+    }
+}
+
+impl<'a, T> ParallelComputeExt<'a> for &'a Vec<T>
+where
+    T: Sync,
+{
+    type Item = &'a T;
+
+    fn par_map<R, F>(self, f: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(&'a T) -> R + Sync,
+    {
+        self.as_slice().par_map(f)
+    }
+
+    fn par_filter<P>(self, predicate: P) -> Vec<&'a T>
+    where
+        P: Fn(&&'a T) -> bool + Sync,
+    {
+        self.as_slice().par_filter(predicate)
+    }
+
+    fn par_for_each<F>(self, f: F)
+    where
+        F: Fn(&'a T) + Sync,
+    {
+        self.as_slice().par_for_each(f)
+    }
+
+    fn par_reduce<R, M, I, C>(self, map: M, identity: I, combine: C) -> R
+    where
+        R: Send,
+        M: Fn(&'a T) -> R + Sync,
+        I: Fn() -> R + Sync,
+        C: Fn(R, R) -> R + Sync,
+    {
+        self.as_slice().par_reduce(map, identity, combine)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use crate::ParallelComputeExt;
+
+    #[test]
+    fn test_vec_par_map() {
+        let result = (0..1000).collect::<Vec<u64>>().par_map(|x| x * 2);
+        assert_eq!(result, (0..1000).map(|x| x * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_vec_par_filter() {
+        let result = (0..1000).collect::<Vec<u64>>().par_filter(|x| x % 3 == 0);
+        assert_eq!(result, (0..1000).filter(|x| x % 3 == 0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_vec_par_for_each() {
+        static SUM: AtomicUsize = AtomicUsize::new(0);
+        (1..=100).collect::<Vec<usize>>().par_for_each(|x| {
+            SUM.fetch_add(x, Ordering::SeqCst);
+        });
+        assert_eq!(SUM.load(Ordering::SeqCst), 5050);
+    }
+
+    #[test]
+    fn test_vec_par_reduce() {
+        let result = (0..100)
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .par_reduce(|x| x, String::new, |a, b| a + &b);
+        assert_eq!(result, (0..100).map(|x| x.to_string()).collect::<String>());
+    }
+
+    #[test]
+    fn test_range_par_map() {
+        let result = (0..1000u64).par_map(|x| x * 2);
+        assert_eq!(result, (0..1000).map(|x| x * 2).collect::<Vec<_>>());
+
+        let result = (-500..500i32).par_map(|x| x * 2);
+        assert_eq!(result, (-500..500).map(|x| x * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_range_par_filter() {
+        let result = (0..1000usize).par_filter(|x| x % 3 == 0);
+        assert_eq!(result, (0..1000).filter(|x| x % 3 == 0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_range_par_for_each() {
+        static SUM: AtomicUsize = AtomicUsize::new(0);
+        (1..101usize).par_for_each(|x| {
+            SUM.fetch_add(x, Ordering::SeqCst);
+        });
+        assert_eq!(SUM.load(Ordering::SeqCst), 5050);
+    }
+
+    #[test]
+    fn test_range_par_reduce() {
+        let result = (1..1_000_001u64).par_reduce(|x| x, || 0, |a, b| a + b);
+        assert_eq!(result, 500_000_500_000);
+    }
+
+    #[test]
+    fn test_slice_chained_calls() {
+        let threshold = 500;
+        let input: Vec<u64> = (0..1000).collect();
+
+        let result = input
+            .as_slice()
+            .par_map(|x| x + threshold)
+            .par_filter(move |x| x % threshold == 0)
+            .par_reduce(|x| x, || 0, |a, b| a + b);
+        assert_eq!(result, 500 + 1000);
+    }
+
+    #[test]
+    fn test_slice_par_filter_borrows() {
+        let threshold = 500;
+        let input: Vec<u64> = (0..1000).collect();
+        let result = input.as_slice().par_filter(|x| **x >= threshold);
+        assert_eq!(result, input[500..].iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_slice_par_for_each() {
+        let sum = AtomicUsize::new(0);
+        let input: Vec<usize> = (1..=100).collect();
+        (&input).par_for_each(|x| {
+            sum.fetch_add(*x, Ordering::SeqCst);
+        });
+        assert_eq!(sum.load(Ordering::SeqCst), 5050);
+    }
+
+    #[test]
+    fn test_slice_par_reduce() {
+        let input: Vec<i32> = vec![3, 9, -4, 12, 7, 12, 0, 5];
+        let max = (&input).par_reduce(|x| *x, || i32::MIN, i32::max);
+        assert_eq!(max, 12);
+    }
+
+    #[test]
+    fn test_slice_par_reduce_sum() {
+        let input: Vec<u64> = (1..=1000).collect();
+        let sum = input.as_slice().par_reduce(|x| *x, || 0, |a, b| a + b);
+        assert_eq!(sum, 500_500);
+    }
+
+    #[test]
+    fn test_slice_par_map_closure_not_send() {
+        let offset = Mutex::new(1000);
+        let input: Vec<u64> = (0..1000).collect();
+
+        // A mutex guard can be shared between threads but not sent to another one
+        let guard = offset.lock().unwrap();
+        let result = input.as_slice().par_map(move |x| x + *guard);
+        assert_eq!(result, (1000..2000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_vec_par_map_borrows() {
+        let factors: Vec<u64> = vec![2, 3];
+        let result = (0..1000)
+            .collect::<Vec<u64>>()
+            .par_map(|x| x * factors[0] * factors[1]);
+        assert_eq!(result, (0..1000).map(|x| x * 6).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,280 @@
+use std::any::Any;
+use std::fmt::{self, Display, Formatter};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::map_chunks;
+
+/// A panic caught while computing the function on one element of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanicInfo {
+    index: usize,
+    message: String,
+}
+
+impl PanicInfo {
+    /// Returns the index of the element in the input.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the panic message, if the panic was raised with a string.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for PanicInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "element {} panicked: {}", self.index, self.message)
+    }
+}
+
+impl std::error::Error for PanicInfo {}
+
+/// Computes the fallible function `f` on each element of `input` in parallel, like
+/// [`compute`](crate::compute), stopping at the first error.
+///
+/// As soon as `f` fails on any element, the threads stop picking up new elements and the error is returned. If `f`
+/// fails on several elements at the same time, any of the errors may be returned.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::try_compute;
+/// let input = vec!["1", "2", "3", "4", "5", "6"];
+/// assert_eq!(try_compute(input, |s| s.parse::<i32>()), Ok(vec![1, 2, 3, 4, 5, 6]));
+///
+/// let input = vec!["1", "2", "three", "4", "5", "6"];
+/// assert!(try_compute(input, |s| s.parse::<i32>()).is_err());
+/// ```
+pub fn try_compute<T, R, E, F>(input: impl IntoIterator<Item = T>, f: F) -> Result<Vec<R>, E>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    E: Send + 'static,
+    F: Fn(T) -> Result<R, E> + Send + Sync + 'static,
+{
+    let failed = AtomicBool::new(false);
+
+    let outputs = map_chunks(input, move |_, chunk| {
+        let mut output = Vec::with_capacity(chunk.len());
+
+        try_chunk(chunk, &f, &failed, |result| output.push(result))
+            .map(|outcome| outcome.map(|()| output))
+    });
+
+    // A chunk is only ever interrupted after another one failed, so the error is always found
+    let mut results = Vec::new();
+    for output in outputs.into_iter().flatten() {
+        results.extend(output?);
+    }
+
+    Ok(results)
+}
+
+/// Calls the fallible function `f` on each element of `input` in parallel, like
+/// [`compute_for_each`](crate::compute_for_each), stopping at the first error.
+///
+/// As soon as `f` fails on any element, the threads stop picking up new elements and the error is returned. If `f`
+/// fails on several elements at the same time, any of the errors may be returned.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::try_compute_for_each;
+/// let result = try_compute_for_each(0..100, |t| if t < 50 { Ok(()) } else { Err(t) });
+/// assert!(result.is_err());
+/// ```
+pub fn try_compute_for_each<T, E, F>(input: impl IntoIterator<Item = T>, f: F) -> Result<(), E>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+    F: Fn(T) -> Result<(), E> + Send + Sync + 'static,
+{
+    let failed = AtomicBool::new(false);
+
+    let outcomes = map_chunks(input, move |_, chunk| try_chunk(chunk, &f, &failed, drop));
+
+    // A chunk is only ever interrupted after another one failed, so the error is always found
+    outcomes.into_iter().flatten().collect()
+}
+
+/// Computes `f` on each element of `chunk`, passing the results to `output`, until `f` fails.
+///
+/// Returns `None` without computing the remaining elements as soon as `failed` is set by another thread, since the
+/// result of this chunk would be discarded anyway.
+fn try_chunk<T, R, E>(
+    chunk: Vec<T>,
+    f: impl Fn(T) -> Result<R, E>,
+    failed: &AtomicBool,
+    mut output: impl FnMut(R),
+) -> Option<Result<(), E>> {
+    for item in chunk {
+        if failed.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        match f(item) {
+            Ok(result) => output(result),
+            Err(error) => {
+                failed.store(true, Ordering::Relaxed);
+                return Some(Err(error));
+            }
+        }
+    }
+
+    Some(Ok(()))
+}
+
+/// Computes the given function `f` on each element of `input` in parallel, like
+/// [`compute`](crate::compute), isolating panics.
+///
+/// A panic in `f` only affects the element it was computing: its outcome is a [`PanicInfo`] carrying the index of the
+/// element and the panic message, while all the other elements are still computed. The panics are still reported by
+/// the panic hook, which prints them to the standard error by default.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_catch_unwind;
+/// let output = compute_catch_unwind(vec![1, 2, 0, 4], |t| 12 / t);
+/// assert_eq!(output[0], Ok(12));
+/// assert_eq!(output[2].as_ref().unwrap_err().index(), 2);
+/// assert_eq!(output[3], Ok(3));
+/// ```
+pub fn compute_catch_unwind<T, R, F>(
+    input: impl IntoIterator<Item = T>,
+    f: F,
+) -> Vec<Result<R, PanicInfo>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let outputs = map_chunks(input, move |offset, chunk| {
+        chunk
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                catch_unwind(AssertUnwindSafe(|| f(item))).map_err(|payload| PanicInfo {
+                    index: offset + index,
+                    message: panic_message(payload.as_ref()),
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+
+    outputs.into_iter().flatten().collect()
+}
+
+/// Extracts the message of a panic payload, which is a string unless the panic was raised with a custom value.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        String::from(*message)
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("Box<dyn Any>")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::{compute_catch_unwind, try_compute, try_compute_for_each};
+
+    #[test]
+    fn test_try_compute_empty_input() {
+        let input: Vec<i32> = vec![];
+        let result = try_compute(input, Ok::<_, ()>);
+        assert_eq!(result, Ok(vec![]));
+    }
+
+    #[test]
+    fn test_try_compute_all_succeed() {
+        let result = try_compute(0..1000, |x| if x >= 0 { Ok(x * 2) } else { Err(x) });
+        assert_eq!(result, Ok((0..1000).map(|x| x * 2).collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn test_try_compute_returns_error() {
+        let result = try_compute(0..1000, |x| {
+            if x == 500 {
+                Err(format!("bad record {x}"))
+            } else {
+                Ok(x)
+            }
+        });
+        assert_eq!(result, Err(String::from("bad record 500")));
+    }
+
+    #[test]
+    fn test_try_compute_stops_early() {
+        let processed = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&processed);
+
+        let result = try_compute(0..1000, move |x| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            if x == 0 {
+                return Err(x);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+            Ok(x)
+        });
+
+        assert_eq!(result, Err(0));
+        assert!(processed.load(Ordering::SeqCst) < 1000);
+    }
+
+    #[test]
+    fn test_try_compute_for_each_all_succeed() {
+        let processed = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&processed);
+
+        let result = try_compute_for_each(0..1000, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, String>(())
+        });
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(processed.load(Ordering::SeqCst), 1000);
+    }
+
+    #[test]
+    fn test_try_compute_for_each_returns_error() {
+        let result = try_compute_for_each(0..1000, |x| if x == 999 { Err(x) } else { Ok(()) });
+        assert_eq!(result, Err(999));
+    }
+
+    #[test]
+    fn test_compute_catch_unwind_isolates_panics() {
+        let result = compute_catch_unwind(0..1000, |x: usize| {
+            if x % 300 == 299 {
+                panic!("poisoned record {x}");
+            }
+            x * 2
+        });
+
+        assert_eq!(result.len(), 1000);
+        for (index, outcome) in result.into_iter().enumerate() {
+            match outcome {
+                Ok(value) => assert_eq!(value, index * 2),
+                Err(panic) => {
+                    assert_eq!(index % 300, 299);
+                    assert_eq!(panic.index(), index);
+                    assert_eq!(panic.message(), format!("poisoned record {index}"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_catch_unwind_custom_payload() {
+        let result = compute_catch_unwind(vec![1], |_: i32| -> i32 { std::panic::panic_any(42) });
+        assert_eq!(result[0].as_ref().unwrap_err().message(), "Box<dyn Any>");
+    }
+}
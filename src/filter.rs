@@ -0,0 +1,87 @@
+use crate::config::map_chunks;
+
+/// Returns the elements of `input` matching `predicate`, evaluated in parallel, in input order.
+///
+/// Each thread drops the rejected elements of its chunk, and the surviving elements of every chunk are then
+/// concatenated.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_filter;
+/// let output = compute_filter(1..=10, |t| t % 3 == 0);
+/// assert_eq!(output, vec![3, 6, 9]);
+/// ```
+pub fn compute_filter<T, P>(input: impl IntoIterator<Item = T>, predicate: P) -> Vec<T>
+where
+    T: Send + 'static,
+    P: Fn(&T) -> bool + Send + Sync + 'static,
+{
+    let outputs = map_chunks(input, move |_, chunk| {
+        chunk.into_iter().filter(&predicate).collect::<Vec<_>>()
+    });
+
+    outputs.into_iter().flatten().collect()
+}
+
+/// Computes the given function `f` on each element of `input` in parallel, keeping only the `Some` results, in input
+/// order.
+///
+/// This avoids computing a vector of `Option<R>` and flattening it afterwards: each thread drops the `None` results
+/// of its chunk, and the remaining results of every chunk are then concatenated.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_filter_map;
+/// let input = vec!["1", "two", "3", "four", "5"];
+/// let output = compute_filter_map(input, |s| s.parse::<i32>().ok());
+/// assert_eq!(output, vec![1, 3, 5]);
+/// ```
+pub fn compute_filter_map<T, R, F>(input: impl IntoIterator<Item = T>, f: F) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> Option<R> + Send + Sync + 'static,
+{
+    let outputs = map_chunks(input, move |_, chunk| {
+        chunk.into_iter().filter_map(&f).collect::<Vec<_>>()
+    });
+
+    outputs.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{compute_filter, compute_filter_map};
+
+    #[test]
+    fn test_compute_filter_empty_input() {
+        let result = compute_filter(Vec::<i32>::new(), |x| *x > 0);
+        assert_eq!(result, vec![]);
+    }
+
+    #[test]
+    fn test_compute_filter_preserves_order() {
+        let result = compute_filter(0..1000, |x| x % 7 == 3);
+        assert_eq!(result, (0..1000).filter(|x| x % 7 == 3).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_compute_filter_none_match() {
+        let result = compute_filter(0..1000, |x| *x < 0);
+        assert_eq!(result, vec![]);
+    }
+
+    #[test]
+    fn test_compute_filter_map_preserves_order() {
+        let result = compute_filter_map(0..1000, |x| (x % 5 == 0).then(|| x.to_string()));
+        assert_eq!(
+            result,
+            (0..1000)
+                .filter(|x| x % 5 == 0)
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::iter::{
+    Chain, Cloned, Copied, Empty, Enumerate, Filter, FilterMap, FlatMap, Flatten, FromFn, Fuse,
+    Inspect, Map, MapWhile, Once, Peekable, Rev, Scan, Skip, SkipWhile, StepBy, Successors, Take,
+    TakeWhile, Zip,
+};
+use std::ops::{Range, RangeInclusive};
+use std::sync::Arc;
+use std::{slice, vec};
+
+use crate::Split;
+
+/// An input accepted by [`compute`](crate::compute), [`Compute::run`](crate::Compute::run) and
+/// [`ThreadPool::compute`](crate::ThreadPool::compute).
+///
+/// Vectors, ranges of integers and `'static` slices are split into chunks without being collected: a vector is split
+/// in place, a range is split into smaller ranges, and a slice into sub-slices whose elements are passed by reference.
+/// The other standard collections and iterator adapters are first collected into a vector. Any other iterator can be
+/// passed as `iter.collect::<Vec<_>>()`, and borrowed slices with [`compute_slice`](crate::compute_slice).
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use simple_parallel_compute::compute;
+/// let squares = compute(1..=4, |t| t * t);
+/// assert_eq!(squares, vec![1, 4, 9, 16]);
+///
+/// let mut lengths = compute(HashSet::from(["a", "bb"]), |word| word.len());
+/// lengths.sort();
+/// assert_eq!(lengths, vec![1, 2]);
+///
+/// static WORDS: [&str; 3] = ["a", "bb", "ccc"];
+/// let lengths = compute(&WORDS[..], |word| word.len());
+/// assert_eq!(lengths, vec![1, 2, 3]);
+/// ```
+pub trait ComputeInput: Sized {
+    /// The type of the elements passed to the computed function.
+    type Item;
+
+    /// Converts the input into a form that can be split into chunks.
+    #[doc(hidden)]
+    fn into_input(self) -> Input<Self::Item>;
+}
+
+/// An input ready to be split into chunks, either owning its elements or producing them from their index.
+pub struct Input<T>(Repr<T>);
+
+enum Repr<T> {
+    Vec(Vec<T>),
+    Indexed {
+        element: Arc<dyn Fn(usize) -> T + Send + Sync>,
+        indices: Range<usize>,
+    },
+}
+
+impl<T> Input<T> {
+    fn indexed(len: usize, element: impl Fn(usize) -> T + Send + Sync + 'static) -> Self {
+        Self(Repr::Indexed {
+            element: Arc::new(element),
+            indices: 0..len,
+        })
+    }
+}
+
+impl<T> Split for Input<T> {
+    fn len(&self) -> usize {
+        match &self.0 {
+            Repr::Vec(vec) => vec.len(),
+            Repr::Indexed { indices, .. } => indices.len(),
+        }
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        match &mut self.0 {
+            Repr::Vec(vec) => Self(Repr::Vec(Split::split_off(vec, at))),
+            Repr::Indexed { element, indices } => {
+                let middle = indices.start + at;
+                let tail = middle..indices.end;
+                indices.end = middle;
+
+                Self(Repr::Indexed {
+                    element: Arc::clone(element),
+                    indices: tail,
+                })
+            }
+        }
+    }
+}
+
+impl<T> IntoIterator for Input<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        match self.0 {
+            Repr::Vec(vec) => IntoIter::Vec(vec.into_iter()),
+            Repr::Indexed { element, indices } => IntoIter::Indexed { element, indices },
+        }
+    }
+}
+
+/// An iterator over the elements of an [`Input`], in order.
+pub enum IntoIter<T> {
+    Vec(vec::IntoIter<T>),
+    Indexed {
+        element: Arc<dyn Fn(usize) -> T + Send + Sync>,
+        indices: Range<usize>,
+    },
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self {
+            IntoIter::Vec(iter) => iter.next(),
+            IntoIter::Indexed { element, indices } => indices.next().map(|index| element(index)),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IntoIter::Vec(iter) => iter.size_hint(),
+            IntoIter::Indexed { indices, .. } => indices.size_hint(),
+        }
+    }
+}
+
+impl<T> ComputeInput for Vec<T> {
+    type Item = T;
+
+    fn into_input(self) -> Input<T> {
+        Input(Repr::Vec(self))
+    }
+}
+
+/// Slices are split into sub-slices, so only `'static` slices can be computed on the pool: use
+/// [`compute_slice`](crate::compute_slice) for borrowed ones.
+impl<T: Sync> ComputeInput for &'static [T] {
+    type Item = &'static T;
+
+    fn into_input(self) -> Input<&'static T> {
+        Input::indexed(self.len(), move |index| &self[index])
+    }
+}
+
+/// Ranges of integers are split into smaller ranges, so their elements are never stored.
+macro_rules! impl_compute_input_for_ranges {
+    ($($t:ty),*) => {
+        $(
+            impl ComputeInput for Range<$t> {
+                type Item = $t;
+
+                fn into_input(self) -> Input<$t> {
+                    let len = (self.end as i128 - self.start as i128).max(0);
+                    let len = usize::try_from(len).expect("the range has more than `usize::MAX` elements");
+
+                    // Every element of the range fits in its type, so the wrapping arithmetic gives the exact result
+                    Input::indexed(len, move |index| self.start.wrapping_add(index as $t))
+                }
+            }
+
+            impl ComputeInput for RangeInclusive<$t> {
+                type Item = $t;
+
+                fn into_input(self) -> Input<$t> {
+                    if self.is_empty() {
+                        return Input(Repr::Vec(Vec::new()));
+                    }
+
+                    let (start, end) = self.into_inner();
+                    let len = end as i128 - start as i128 + 1;
+                    let len = usize::try_from(len).expect("the range has more than `usize::MAX` elements");
+
+                    Input::indexed(len, move |index| start.wrapping_add(index as $t))
+                }
+            }
+        )*
+    };
+}
+
+impl_compute_input_for_ranges!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// The other collections and iterators are collected into a vector, since their elements can't be split in place.
+macro_rules! impl_compute_input_by_collecting {
+    ($([$($generics:tt)*] $input:ty),* $(,)?) => {
+        $(
+            impl<$($generics)*> ComputeInput for $input
+            where
+                $input: IntoIterator,
+            {
+                type Item = <$input as IntoIterator>::Item;
+
+                fn into_input(self) -> Input<Self::Item> {
+                    Input(Repr::Vec(self.into_iter().collect()))
+                }
+            }
+        )*
+    };
+}
+
+impl_compute_input_by_collecting!(
+    [T, const N: usize] [T; N],
+    [T] Option<T>,
+    [T] VecDeque<T>,
+    [T] LinkedList<T>,
+    [T] BinaryHeap<T>,
+    [T] BTreeSet<T>,
+    [K, V] BTreeMap<K, V>,
+    [T, S] HashSet<T, S>,
+    [K, V, S] HashMap<K, V, S>,
+    [T] vec::IntoIter<T>,
+    ['a, T] slice::Iter<'a, T>,
+    ['a, T] Box<dyn Iterator<Item = T> + 'a>,
+    ['a, T] Box<dyn Iterator<Item = T> + Send + 'a>,
+    [T] Once<T>,
+    [T] Empty<T>,
+    [F] FromFn<F>,
+    [T, F] Successors<T, F>,
+    [A, B] Chain<A, B>,
+    [I] Cloned<I>,
+    [I] Copied<I>,
+    [I] Enumerate<I>,
+    [I, P] Filter<I, P>,
+    [I, F] FilterMap<I, F>,
+    [I, U: IntoIterator, F] FlatMap<I, U, F>,
+    [I: Iterator<Item = U>, U: IntoIterator] Flatten<I>,
+    [I] Fuse<I>,
+    [I, F] Inspect<I, F>,
+    [I, F] Map<I, F>,
+    [I, P] MapWhile<I, P>,
+    [I: Iterator] Peekable<I>,
+    [I] Rev<I>,
+    [I, St, F] Scan<I, St, F>,
+    [I] Skip<I>,
+    [I, P] SkipWhile<I, P>,
+    [I] StepBy<I>,
+    [I] Take<I>,
+    [I, P] TakeWhile<I, P>,
+    [A, B] Zip<A, B>,
+);
+
+#[cfg(test)]
+mod test {
+    use crate::input::ComputeInput;
+    use crate::{split_into_chunks, Split};
+
+    fn chunks<I: ComputeInput>(input: I, chunk_size: usize) -> Vec<Vec<I::Item>> {
+        split_into_chunks(input.into_input(), chunk_size)
+            .into_iter()
+            .map(|chunk| chunk.into_iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_ranges_into_chunks() {
+        assert_eq!(
+            chunks(0..10u64, 3),
+            vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8], vec![9]]
+        );
+        assert_eq!(chunks(5..5usize, 3), Vec::<Vec<usize>>::new());
+        let reversed = std::ops::Range {
+            start: 10i32,
+            end: 0,
+        };
+        assert_eq!(chunks(reversed, 3), Vec::<Vec<i32>>::new());
+
+        let halves = chunks(i8::MIN..i8::MAX, 200);
+        assert_eq!(halves[0], (i8::MIN..72).collect::<Vec<_>>());
+        assert_eq!(halves[1], (72..i8::MAX).collect::<Vec<_>>());
+        assert_eq!(Split::len(&(i64::MIN..i64::MAX).into_input()), usize::MAX);
+    }
+
+    #[test]
+    fn test_split_inclusive_ranges_into_chunks() {
+        assert_eq!(chunks(1..=5u8, 2), vec![vec![1, 2], vec![3, 4], vec![5]]);
+        assert_eq!(Split::len(&(u8::MIN..=u8::MAX).into_input()), 256);
+        assert_eq!(chunks(u8::MAX - 1..=u8::MAX, 1), vec![vec![254], vec![255]]);
+
+        let mut exhausted = 1..=1;
+        exhausted.next();
+        assert_eq!(chunks(exhausted, 1), Vec::<Vec<i32>>::new());
+    }
+
+    #[test]
+    fn test_split_static_slices_into_chunks() {
+        static INPUT: [i32; 5] = [1, 2, 3, 4, 5];
+        assert_eq!(
+            chunks(&INPUT[..], 2),
+            vec![vec![&1, &2], vec![&3, &4], vec![&5]]
+        );
+    }
+
+    #[test]
+    fn test_split_collected_inputs_into_chunks() {
+        assert_eq!(chunks([1, 2, 3], 2), vec![vec![1, 2], vec![3]]);
+        assert_eq!(chunks(Some(1), 2), vec![vec![1]]);
+        assert_eq!(
+            chunks((0..4).map(|x| x * 10).rev(), 3),
+            vec![vec![30, 20, 10], vec![0]]
+        );
+    }
+}
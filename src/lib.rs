@@ -1,12 +1,53 @@
-use std::thread::{available_parallelism, spawn};
+mod cancel;
+mod config;
+mod error;
+mod ext;
+mod fallible;
+mod filter;
+mod input;
+mod pool;
+mod progress;
+mod reduce;
+mod scan;
+mod slice;
+mod sort;
+mod stream;
+mod timeout;
+
+use config::map_chunks;
+
+pub use cancel::{compute_cancellable, CancellationToken};
+pub use config::{Compute, Fallback, Schedule, THREADS_ENV_VAR};
+pub use error::Error;
+pub use ext::ParallelComputeExt;
+pub use fallible::{compute_catch_unwind, try_compute, try_compute_for_each, PanicInfo};
+pub use filter::{compute_filter, compute_filter_map};
+pub use input::ComputeInput;
+pub use pool::ThreadPool;
+pub use progress::{compute_with_progress, Progress};
+pub use reduce::{compute_fold, compute_reduce};
+pub use scan::{compute_scan, compute_scan_exclusive};
+pub use slice::{compute_in_place, compute_slice};
+pub use sort::{par_sort, par_sort_by_key, par_sort_unstable};
+pub use stream::{compute_bounded, compute_stream, compute_stream_ordered, OrderedStream, Stream};
+pub use timeout::{compute_with_timeout, TimedOut};
 
 const THRESHOLD: usize = 5;
 
-/// Computes the given function `f` on each element of the input vector `input`
-/// in parallel using multiple threads.
+/// Computes the given function `f` on each element of the input collection or iterator `input`
+/// in parallel using the threads of the [global](ThreadPool::global) [`ThreadPool`].
 ///
 /// If the input is small enough (less than the `THRESHOLD` constant), the computation is
-/// performed in the main thread instead of spawning new threads.
+/// performed in the calling thread instead of being dispatched onto the pool. The output is always in input order,
+/// and a panic in `f` is propagated to the caller.
+///
+/// The input is split into chunks by moving its elements, so they are never cloned. Vectors, ranges of integers and
+/// `'static` slices are split without being collected, while sets, maps and iterator chains are first collected into a
+/// vector: see [`ComputeInput`] for the accepted inputs. To process borrowed slices, use [`compute_slice`].
+///
+/// Use [`Compute`] to tune the number of threads, the threshold or the chunk size.
+///
+/// The function may capture its environment: a single instance of `f` is shared between all the spawned threads.
 ///
 /// # Examples
 ///
@@ -15,44 +56,216 @@ const THRESHOLD: usize = 5;
 /// let input = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
 /// let output = compute(input, |t| t * 2);
 /// assert_eq!(output, vec![2, 4, 6, 8, 10, 12, 14, 16, 18, 20]);
+///
+/// let output = compute(0..100, |i| i * 2);
+/// assert_eq!(output, (0..100).map(|i| i * 2).collect::<Vec<_>>());
+/// ```
+pub fn compute<T, R, F>(input: impl ComputeInput<Item = T>, f: F) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    Compute::new()
+        .run(input, f)
+        .expect("the default configuration is valid")
+}
+
+/// Computes the given function `f` on each element of `input` in parallel, where each element produces any number of
+/// results, and concatenates all the results in input order.
+///
+/// The results are expanded inside the threads, so only one vector of results per chunk is sent back.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_flat_map;
+/// let documents = vec!["the quick fox", "jumps", "over the lazy dog"];
+/// let words = compute_flat_map(documents, |document| document.split(' ').collect::<Vec<_>>());
+/// assert_eq!(words, vec!["the", "quick", "fox", "jumps", "over", "the", "lazy", "dog"]);
+/// ```
+pub fn compute_flat_map<T, R, U, F>(input: impl IntoIterator<Item = T>, f: F) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    U: IntoIterator<Item = R>,
+    F: Fn(T) -> U + Send + Sync + 'static,
+{
+    let outputs = map_chunks(input, move |_, chunk| {
+        chunk.into_iter().flat_map(&f).collect::<Vec<_>>()
+    });
+
+    outputs.into_iter().flatten().collect()
+}
+
+/// Calls the given function `f` on each element of `input` in parallel, returning once all the calls are done.
+///
+/// This is meant for side effects such as writing files or sending metrics: unlike [`compute`], no results are
+/// collected. Use [`try_compute_for_each`] if `f` can fail.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use simple_parallel_compute::compute_for_each;
+/// static TOTAL: AtomicUsize = AtomicUsize::new(0);
+/// compute_for_each(1..=10, |t| {
+///     TOTAL.fetch_add(t, Ordering::Relaxed);
+/// });
+/// assert_eq!(TOTAL.load(Ordering::Relaxed), 55);
 /// ```
-pub fn compute<T, R>(input: Vec<T>, f: fn(t: T) -> R) -> Vec<R>
+pub fn compute_for_each<T, F>(input: impl IntoIterator<Item = T>, f: F)
 where
-    T: Send + Clone + 'static,
+    T: Send + 'static,
+    F: Fn(T) + Send + Sync + 'static,
+{
+    map_chunks(input, move |_, chunk| chunk.into_iter().for_each(&f));
+}
+
+/// Computes the given function `f` on each pair of elements of `a` and `b` with the same index in parallel.
+///
+/// Both inputs are split on the same chunk boundaries, so they don't need to be zipped into a vector of pairs first.
+///
+/// # Errors
+///
+/// Returns [`Error::LengthMismatch`] if the inputs don't have the same number of elements.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_zip;
+/// let features = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+/// let weights = vec![0.5, 0.5, 2.0, 2.0, 1.0];
+/// let output = compute_zip(features, weights, |feature, weight| feature * weight).unwrap();
+/// assert_eq!(output, vec![0.5, 1.0, 6.0, 8.0, 5.0]);
+///
+/// assert!(compute_zip(vec![1, 2, 3], vec![1, 2], |a, b| a + b).is_err());
+/// ```
+pub fn compute_zip<A, B, R, F>(
+    a: impl IntoIterator<Item = A>,
+    b: impl IntoIterator<Item = B>,
+    f: F,
+) -> Result<Vec<R>, Error>
+where
+    A: Send + 'static,
+    B: Send + 'static,
     R: Send + 'static,
+    F: Fn(A, B) -> R + Send + Sync + 'static,
 {
-    let input_size = input.len();
+    let a: Vec<A> = a.into_iter().collect();
+    let b: Vec<B> = b.into_iter().collect();
 
-    // If the input is small enough, just compute it in the main thread
-    if input_size < THRESHOLD {
-        return input.into_iter().map(f).collect();
+    if a.len() != b.len() {
+        return Err(Error::LengthMismatch {
+            left: a.len(),
+            right: b.len(),
+        });
     }
 
-    let threads_count = available_parallelism()
-        .expect("cannot get parallelism")
-        .get();
+    let outputs = Compute::new()
+        .map_split((a, b), move |_, (a, b)| {
+            a.into_iter()
+                .zip(b)
+                .map(|(a, b)| f(a, b))
+                .collect::<Vec<_>>()
+        })
+        .expect("the default configuration is valid");
+
+    Ok(outputs.into_iter().flatten().collect())
+}
 
-    // The chunk size is calculated that way because we want to ensure that each chunk has roughly the same number of
-    // elements, and that all elements are distributed evenly among the threads.
-    let chunk_size = (input_size + threads_count - 1) / threads_count;
+/// An input that can be split into chunks by moving its elements.
+pub(crate) trait Split: Sized {
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
 
-    let mut thread_handles = Vec::with_capacity(threads_count);
+    /// Returns `true` if there are no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-    input.chunks(chunk_size).for_each(|chunk| {
-        let chunk = chunk.to_vec();
+    /// Moves the elements from `at` to the end into a new input, releasing the memory they used.
+    fn split_off(&mut self, at: usize) -> Self;
+}
 
-        thread_handles.push(spawn(move || chunk.into_iter().map(f).collect::<Vec<_>>()));
-    });
+impl<T> Split for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
 
-    thread_handles
-        .into_iter()
-        .flat_map(|handle| handle.join().unwrap())
-        .collect()
+    fn split_off(&mut self, at: usize) -> Self {
+        let tail = Vec::split_off(self, at);
+        self.shrink_to_fit();
+        tail
+    }
+}
+
+/// Slices are split into sub-slices, so their elements are not moved.
+impl<T> Split for &[T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        let (head, tail) = self.split_at(at);
+        *self = head;
+        tail
+    }
+}
+
+/// Mutable slices are split into disjoint mutable sub-slices.
+impl<T> Split for &mut [T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        let (head, tail) = std::mem::take(self).split_at_mut(at);
+        *self = head;
+        tail
+    }
+}
+
+/// Two inputs of the same length, split on the same boundaries.
+impl<A: Split, B: Split> Split for (A, B) {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        (self.0.split_off(at), self.1.split_off(at))
+    }
+}
+
+/// Splits `input` into chunks of `chunk_size` elements (the last one may be shorter) by moving the elements.
+///
+/// Chunks are cut from the back of the input, which is shrunk after each cut, so the elements are only ever stored
+/// once in memory.
+pub(crate) fn split_into_chunks<S: Split>(mut input: S, chunk_size: usize) -> Vec<S> {
+    let mut chunks = Vec::with_capacity(input.len().div_ceil(chunk_size));
+
+    while input.len() > chunk_size {
+        let last_chunk_start = (input.len() - 1) / chunk_size * chunk_size;
+        chunks.push(input.split_off(last_chunk_start));
+    }
+
+    if !input.is_empty() {
+        chunks.push(input);
+    }
+
+    chunks.reverse();
+    chunks
 }
 
 #[cfg(test)]
 mod test {
-    use crate::compute;
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::{
+        compute, compute_flat_map, compute_for_each, compute_zip, split_into_chunks, Error,
+    };
 
     #[test]
     fn test_compute_static_empty_input() {
@@ -104,6 +317,142 @@ mod test {
         assert_eq!(result, vec![1, 2, 6, 24, 120]);
     }
 
+    #[test]
+    fn test_compute_capturing_closure() {
+        let offset = 100;
+        let lookup = [10, 20, 30];
+        let input = vec![0, 1, 2, 0, 1, 2, 0, 1, 2, 0];
+        let result = compute(input, move |x| lookup[x] + offset);
+        assert_eq!(
+            result,
+            vec![110, 120, 130, 110, 120, 130, 110, 120, 130, 110]
+        );
+    }
+
+    #[test]
+    fn test_compute_non_clone_input() {
+        let input: Vec<Box<dyn Fn() -> usize + Send>> = (0..20usize)
+            .map(|i| Box::new(move || i * 3) as Box<dyn Fn() -> usize + Send>)
+            .collect();
+        let result = compute(input, |f| f());
+        assert_eq!(result, (0..20usize).map(|i| i * 3).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_compute_range_input() {
+        let result = compute(0..1000u64, |x| x * 2);
+        assert_eq!(result, (0..1000).map(|x| x * 2).collect::<Vec<_>>());
+
+        let result = compute(1..=10, |x| x * x);
+        assert_eq!(result, vec![1, 4, 9, 16, 25, 36, 49, 64, 81, 100]);
+    }
+
+    #[test]
+    fn test_compute_set_input() {
+        let input: HashSet<i32> = (0..100).collect();
+        let mut result = compute(input, |x| x + 1);
+        result.sort();
+        assert_eq!(result, (1..101).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_compute_iterator_chain_input() {
+        let words = ["alpha", "beta", "gamma", "delta", "epsilon", "zeta"];
+        let input = words
+            .iter()
+            .map(|word| word.to_uppercase())
+            .filter(|word| word.len() > 4);
+        let result = compute(input, |word| word.len());
+        assert_eq!(result, vec![5, 5, 5, 7]);
+    }
+
+    #[test]
+    fn test_compute_flat_map_variable_lengths() {
+        let result = compute_flat_map(0..100usize, |x| vec![x; x % 4]);
+        assert_eq!(
+            result,
+            (0..100usize)
+                .flat_map(|x| vec![x; x % 4])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_compute_flat_map_empty_outputs() {
+        let result = compute_flat_map(0..100, |_| None::<i32>);
+        assert_eq!(result, vec![]);
+    }
+
+    #[test]
+    fn test_compute_for_each_visits_every_element() {
+        let visited = Arc::new((0..1000).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>());
+        let counters = Arc::clone(&visited);
+
+        compute_for_each(0..1000, move |x: usize| {
+            counters[x].fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert!(visited
+            .iter()
+            .all(|count| count.load(Ordering::SeqCst) == 1));
+    }
+
+    #[test]
+    fn test_compute_zip() {
+        let result = compute_zip(0..1000, (0..1000).rev(), |a, b| a + b).unwrap();
+        assert_eq!(result, vec![999; 1000]);
+    }
+
+    #[test]
+    fn test_compute_zip_different_types() {
+        let labels: Vec<String> = (0..100).map(|x| format!("item-{x}")).collect();
+        let result = compute_zip(labels, 0..100usize, |label, x| label.len() + x).unwrap();
+        assert_eq!(
+            result,
+            (0..100usize)
+                .map(|x| format!("item-{x}").len() + x)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_compute_zip_length_mismatch() {
+        let result = compute_zip(0..10, 0..12, |a, b| a + b);
+        assert!(matches!(
+            result,
+            Err(Error::LengthMismatch {
+                left: 10,
+                right: 12
+            })
+        ));
+    }
+
+    #[test]
+    fn test_split_pairs_into_chunks() {
+        assert_eq!(
+            split_into_chunks(((1..=5).collect(), "abcde".chars().collect()), 2),
+            vec![
+                (vec![1, 2], vec!['a', 'b']),
+                (vec![3, 4], vec!['c', 'd']),
+                (vec![5], vec!['e'])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_into_chunks() {
+        assert_eq!(
+            split_into_chunks(Vec::<i32>::new(), 3),
+            Vec::<Vec<i32>>::new()
+        );
+        assert_eq!(split_into_chunks(vec![1, 2], 3), vec![vec![1, 2]]);
+        assert_eq!(split_into_chunks(vec![1, 2, 3], 3), vec![vec![1, 2, 3]]);
+        assert_eq!(
+            split_into_chunks((1..=10).collect::<Vec<_>>(), 3),
+            vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9], vec![10]]
+        );
+    }
+
     #[test]
     fn test_compute_static_long_computation() {
         let input = vec![1, 2, 3, 4, 5];
@@ -1,9 +1,40 @@
-use simple_parallel_compute::compute;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use simple_parallel_compute::{compute_with_progress, Progress};
+
+const BAR_WIDTH: usize = 40;
 
 fn main() {
-    let input = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+    let input: Vec<u64> = (1..=100).collect();
 
-    let output = compute(input, |t| t * 2);
+    let output = compute_with_progress(
+        input,
+        |t| {
+            // Pretend each element takes a while to compute
+            thread::sleep(Duration::from_millis(20));
+            t * 2
+        },
+        draw_progress_bar,
+    );
+    eprintln!();
 
     println!("{:?}", output);
 }
+
+/// Redraws the progress bar on the current line of the standard error.
+fn draw_progress_bar(progress: Progress) {
+    let filled = BAR_WIDTH * progress.completed() / progress.total();
+
+    eprint!(
+        "\r[{}{}] {}/{} ({:.1}s, {:.1} items/s)",
+        "#".repeat(filled),
+        " ".repeat(BAR_WIDTH - filled),
+        progress.completed(),
+        progress.total(),
+        progress.elapsed().as_secs_f64(),
+        progress.throughput()
+    );
+    let _ = io::stderr().flush();
+}
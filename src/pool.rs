@@ -0,0 +1,254 @@
+use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+
+use crate::config::default_threads;
+use crate::{Compute, ComputeInput};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+thread_local! {
+    static IS_WORKER_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A fixed set of worker threads that pick up jobs from a shared queue in submission order.
+///
+/// The threads are spawned once and reused by every computation dispatched onto the pool. Dropping the pool (or
+/// calling [`ThreadPool::shutdown`]) waits for the already queued jobs to finish and joins the worker threads.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::ThreadPool;
+/// let pool = ThreadPool::new(4);
+/// let output = pool.compute(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10], |t| t * 2);
+/// assert_eq!(output, vec![2, 4, 6, 8, 10, 12, 14, 16, 18, 20]);
+/// pool.shutdown();
+/// ```
+pub struct ThreadPool {
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    /// Creates a pool with `threads` worker threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero or if a worker thread cannot be spawned.
+    pub fn new(threads: usize) -> Self {
+        assert!(threads > 0, "a thread pool needs at least one thread");
+
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..threads)
+            .map(|index| {
+                let receiver = Arc::clone(&receiver);
+
+                thread::Builder::new()
+                    .name(format!("simple-parallel-compute-{index}"))
+                    .spawn(move || run_worker(&receiver))
+                    .expect("cannot spawn worker thread")
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    /// Returns the global pool used by [`compute`](crate::compute), creating it on first use with one thread per
+    /// available CPU.
+    ///
+    /// The number of threads can be overridden with the [`THREADS_ENV_VAR`](crate::THREADS_ENV_VAR) environment
+    /// variable. If the number of available CPUs cannot be determined, the pool has a single thread.
+    ///
+    /// The global pool lives for the whole duration of the program and is never shut down.
+    pub fn global() -> &'static ThreadPool {
+        static GLOBAL: OnceLock<ThreadPool> = OnceLock::new();
+
+        GLOBAL.get_or_init(|| ThreadPool::new(default_threads().unwrap_or(1)))
+    }
+
+    /// Returns the number of worker threads in the pool.
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Queues `job` to be run by one of the worker threads.
+    ///
+    /// A panic inside the job is caught, so the worker thread stays available for the next jobs.
+    pub fn execute<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.sender
+            .as_ref()
+            .expect("the thread pool is shut down")
+            .send(Box::new(job))
+            .expect("the thread pool workers have stopped");
+    }
+
+    /// Computes the given function `f` on each element of `input` in parallel on the pool threads.
+    ///
+    /// This behaves exactly like [`compute`](crate::compute), which uses the [global](ThreadPool::global) pool. Use
+    /// [`Compute::pool`] to further configure the computation.
+    pub fn compute<T, R, F>(&self, input: impl ComputeInput<Item = T>, f: F) -> Vec<R>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        Compute::new()
+            .pool(self)
+            .run(input, f)
+            .expect("the default configuration is valid")
+    }
+
+    /// Waits for all the queued jobs to finish and stops the worker threads.
+    ///
+    /// This is the same as dropping the pool, but makes the intent explicit.
+    pub fn shutdown(self) {}
+
+    /// Queues `f` and returns a handle to wait for its result.
+    pub(crate) fn spawn<R, F>(&self, f: F) -> TaskHandle<R>
+    where
+        R: Send + 'static,
+        F: FnOnce() -> R + Send + 'static,
+    {
+        let (sender, receiver) = channel();
+
+        self.execute(move || {
+            // The receiver may be gone if the caller stopped waiting, in which case the result is discarded.
+            let _ = sender.send(catch_unwind(AssertUnwindSafe(f)));
+        });
+
+        TaskHandle { receiver }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // Closing the channel makes the workers exit once the queue is drained.
+        drop(self.sender.take());
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// A handle to the result of a job queued with [`ThreadPool::spawn`].
+pub(crate) struct TaskHandle<R> {
+    receiver: Receiver<thread::Result<R>>,
+}
+
+impl<R> TaskHandle<R> {
+    /// Waits for the job to finish, returning the panic payload if it panicked.
+    pub(crate) fn join(self) -> thread::Result<R> {
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err(Box::new("the job was dropped before completing")))
+    }
+}
+
+/// Returns `true` if the current thread is a worker thread of a [`ThreadPool`].
+pub(crate) fn is_worker_thread() -> bool {
+    IS_WORKER_THREAD.with(Cell::get)
+}
+
+fn run_worker(receiver: &Mutex<Receiver<Job>>) {
+    IS_WORKER_THREAD.with(|is_worker_thread| is_worker_thread.set(true));
+
+    loop {
+        // The lock is released as soon as a job is received, so the other workers can pick up jobs while it runs.
+        let job = receiver.lock().unwrap().recv();
+
+        match job {
+            Ok(job) => {
+                let _ = catch_unwind(AssertUnwindSafe(job));
+            }
+            Err(_) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::{compute, ThreadPool};
+
+    #[test]
+    fn test_thread_pool_compute_preserves_order() {
+        let pool = ThreadPool::new(3);
+        let result = pool.compute(0..1000, |x| x * 2);
+        assert_eq!(result, (0..1000).map(|x| x * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_thread_pool_is_reused_between_calls() {
+        let pool = ThreadPool::new(2);
+
+        for _ in 0..100 {
+            let names = pool.compute(0..10, |_| std::thread::current().name().unwrap().to_owned());
+            assert!(names
+                .iter()
+                .all(|name| name.starts_with("simple-parallel-compute-")));
+        }
+
+        assert_eq!(pool.threads(), 2);
+    }
+
+    #[test]
+    fn test_thread_pool_shutdown_waits_for_queued_jobs() {
+        let pool = ThreadPool::new(2);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..50 {
+            let counter = Arc::clone(&counter);
+            pool.execute(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        pool.shutdown();
+        assert_eq!(counter.load(Ordering::SeqCst), 50);
+    }
+
+    #[test]
+    fn test_thread_pool_survives_panicking_job() {
+        let pool = ThreadPool::new(1);
+        pool.execute(|| panic!("job failure"));
+        let result = pool.compute(0..10, |x| x + 1);
+        assert_eq!(result, (1..11).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "item failure")]
+    fn test_thread_pool_compute_propagates_panic() {
+        let pool = ThreadPool::new(2);
+        pool.compute(0..10, |x: i32| {
+            if x == 7 {
+                panic!("item failure");
+            }
+            x
+        });
+    }
+
+    #[test]
+    fn test_thread_pool_nested_compute() {
+        let pool = ThreadPool::new(1);
+        let result = pool.compute(0..10, |x: i32| {
+            compute((0..10).collect::<Vec<_>>(), move |y| x * y)
+                .iter()
+                .sum::<i32>()
+        });
+        assert_eq!(result, (0..10).map(|x| x * 45).collect::<Vec<_>>());
+    }
+}
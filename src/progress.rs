@@ -0,0 +1,199 @@
+use std::panic::resume_unwind;
+use std::time::{Duration, Instant};
+
+use crate::config::spawn_each;
+
+/// A snapshot of the progress of a computation started with [`compute_with_progress`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    completed: usize,
+    total: usize,
+    elapsed: Duration,
+}
+
+impl Progress {
+    /// Returns the number of elements computed so far.
+    pub fn completed(&self) -> usize {
+        self.completed
+    }
+
+    /// Returns the number of elements of the input.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the time elapsed since the computation started.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the average number of elements computed per second since the computation started.
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.completed as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Computes the given function `f` on each element of `input` in parallel, like [`compute`](crate::compute), calling
+/// `on_progress` every time an element is computed.
+///
+/// `on_progress` runs in the calling thread while the computation runs on the pool, even for small inputs, so it
+/// doesn't need to be thread-safe and always sees the number of completed elements grow one by one, up to the total.
+/// A panic in `f` stops the computation of the remaining elements and is propagated to the caller.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_with_progress;
+/// let mut last_completed = 0;
+/// let output = compute_with_progress(vec![1, 2, 3, 4, 5, 6], |t| t * 2, |progress| {
+///     last_completed = progress.completed();
+///     assert_eq!(progress.total(), 6);
+/// });
+/// assert_eq!(output, vec![2, 4, 6, 8, 10, 12]);
+/// assert_eq!(last_completed, 6);
+/// ```
+pub fn compute_with_progress<T, R, F, P>(
+    input: impl IntoIterator<Item = T>,
+    f: F,
+    mut on_progress: P,
+) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+    P: FnMut(Progress),
+{
+    let start = Instant::now();
+    let input: Vec<T> = input.into_iter().collect();
+    let total = input.len();
+
+    let receiver = spawn_each(input, f, || false);
+    let mut results: Vec<Option<R>> = (0..total).map(|_| None).collect();
+
+    // The channel is closed once every element is computed. A panic drops the receiver while unwinding, which makes the
+    // other threads stop computing the remaining elements.
+    for (completed, message) in (1..).zip(receiver) {
+        let (index, result) = message.unwrap_or_else(|payload| resume_unwind(payload));
+        results[index] = Some(result);
+
+        on_progress(Progress {
+            completed,
+            total,
+            elapsed: start.elapsed(),
+        });
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every element is computed"))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::panic::catch_unwind;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::{compute_with_progress, Progress};
+
+    #[test]
+    fn test_compute_with_progress_reports_every_element() {
+        let mut reports = Vec::new();
+        let output = compute_with_progress(0..1000, |x| x * 2, |progress| reports.push(progress));
+
+        assert_eq!(output, (0..1000).map(|x| x * 2).collect::<Vec<_>>());
+        assert_eq!(reports.len(), 1000);
+        for (index, progress) in reports.iter().enumerate() {
+            assert_eq!(progress.completed(), index + 1);
+            assert_eq!(progress.total(), 1000);
+        }
+        assert!(reports
+            .windows(2)
+            .all(|pair| pair[0].elapsed() <= pair[1].elapsed()));
+    }
+
+    #[test]
+    fn test_compute_with_progress_empty_input() {
+        let input: Vec<i32> = vec![];
+        let mut reported = false;
+        let output = compute_with_progress(input, |x| x, |_| reported = true);
+
+        assert_eq!(output, vec![]);
+        assert!(!reported);
+    }
+
+    #[test]
+    #[should_panic(expected = "bad element")]
+    fn test_compute_with_progress_propagates_panics() {
+        compute_with_progress(
+            0..100,
+            |x| if x == 50 { panic!("bad element") } else { x },
+            |_| {},
+        );
+    }
+
+    #[test]
+    fn test_compute_with_progress_stops_on_panic() {
+        let processed = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&processed);
+
+        let result = catch_unwind(|| {
+            compute_with_progress(
+                0..1000,
+                move |x| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    if x == 0 {
+                        panic!("bad element");
+                    }
+                    std::thread::sleep(Duration::from_millis(1));
+                    x
+                },
+                |_| {},
+            )
+        });
+
+        assert!(result.is_err());
+        assert!(processed.load(Ordering::SeqCst) < 1000);
+    }
+
+    #[test]
+    fn test_compute_with_progress_small_input_reports_while_running() {
+        let mut elapsed = Vec::new();
+        compute_with_progress(
+            vec![0, 300],
+            |x| {
+                std::thread::sleep(Duration::from_millis(x));
+                x
+            },
+            |progress| elapsed.push(progress.elapsed()),
+        );
+
+        // The fast element is reported as soon as it is computed, not once the slow one is
+        assert_eq!(elapsed.len(), 2);
+        assert!(elapsed[0] < Duration::from_millis(150));
+    }
+
+    #[test]
+    fn test_progress_throughput() {
+        let progress = Progress {
+            completed: 500,
+            total: 1000,
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(progress.throughput(), 250.0);
+
+        let progress = Progress {
+            completed: 0,
+            total: 1000,
+            elapsed: Duration::ZERO,
+        };
+        assert_eq!(progress.throughput(), 0.0);
+    }
+}
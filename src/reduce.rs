@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use crate::config::map_chunks;
+
+/// Computes the function `map` on each element of `input` in parallel, and reduces the results into
+/// a single value with the `combine` function.
+///
+/// Each thread reduces its own chunk, starting from `identity()`, and only the partial result of every chunk is sent
+/// back to be combined, in input order. `combine` must therefore be associative, and `identity()` must be a neutral
+/// element for it (`combine(identity(), x) == x`), but `combine` doesn't need to be commutative.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_reduce;
+/// let sum_of_squares = compute_reduce(1..=10, |t: u64| t * t, || 0, |a, b| a + b);
+/// assert_eq!(sum_of_squares, 385);
+/// ```
+pub fn compute_reduce<T, R, M, I, C>(
+    input: impl IntoIterator<Item = T>,
+    map: M,
+    identity: I,
+    combine: C,
+) -> R
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    M: Fn(T) -> R + Send + Sync + 'static,
+    I: Fn() -> R + Send + Sync + 'static,
+    C: Fn(R, R) -> R + Send + Sync + 'static,
+{
+    let identity = Arc::new(identity);
+    let combine = Arc::new(combine);
+
+    let partials = {
+        let identity = Arc::clone(&identity);
+        let combine = Arc::clone(&combine);
+
+        map_chunks(input, move |_, chunk| {
+            chunk.into_iter().map(&map).fold(identity(), &*combine)
+        })
+    };
+
+    partials.into_iter().fold(identity(), &*combine)
+}
+
+/// Folds the elements of `input` in parallel into per-thread accumulators, and merges them into a
+/// single value with the `merge` function.
+///
+/// Each thread starts from its own accumulator created by `init()`, and folds the elements of its chunk into it with
+/// `fold`, so no intermediate vector of results is materialized. The accumulators are then merged in input order, so
+/// `merge` must be associative but doesn't need to be commutative.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use simple_parallel_compute::compute_fold;
+/// let words = vec!["a", "b", "a", "c", "b", "a"];
+/// let histogram = compute_fold(
+///     words,
+///     HashMap::new,
+///     |mut counts, word| {
+///         *counts.entry(word).or_insert(0) += 1;
+///         counts
+///     },
+///     |mut counts, other| {
+///         for (word, count) in other {
+///             *counts.entry(word).or_insert(0) += count;
+///         }
+///         counts
+///     },
+/// );
+/// assert_eq!(histogram, HashMap::from([("a", 3), ("b", 2), ("c", 1)]));
+/// ```
+pub fn compute_fold<T, A, I, F, M>(
+    input: impl IntoIterator<Item = T>,
+    init: I,
+    fold: F,
+    merge: M,
+) -> A
+where
+    T: Send + 'static,
+    A: Send + 'static,
+    I: Fn() -> A + Send + Sync + 'static,
+    F: Fn(A, T) -> A + Send + Sync + 'static,
+    M: Fn(A, A) -> A,
+{
+    let partials = map_chunks(input, move |_, chunk| chunk.into_iter().fold(init(), &fold));
+
+    // The input is never split into zero chunks, even when it is empty
+    partials
+        .into_iter()
+        .reduce(merge)
+        .expect("there is at least one chunk")
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::{compute_fold, compute_reduce};
+
+    #[test]
+    fn test_compute_reduce_empty_input() {
+        let result = compute_reduce(Vec::<i32>::new(), |x| x, || 0, |a, b| a + b);
+        assert_eq!(result, 0);
+    }
+
+    #[test]
+    fn test_compute_reduce_sum() {
+        let result = compute_reduce(1..=1000, |x: u64| x * 2, || 0, |a, b| a + b);
+        assert_eq!(result, 1000 * 1001);
+    }
+
+    #[test]
+    fn test_compute_reduce_preserves_order() {
+        let result = compute_reduce(0..100, |x: i32| x.to_string(), String::new, |a, b| a + &b);
+        assert_eq!(result, (0..100).map(|x| x.to_string()).collect::<String>());
+    }
+
+    #[test]
+    fn test_compute_fold_empty_input() {
+        let result = compute_fold(
+            Vec::<i32>::new(),
+            Vec::new,
+            |mut acc, x| {
+                acc.push(x);
+                acc
+            },
+            |mut a, b| {
+                a.extend(b);
+                a
+            },
+        );
+        assert_eq!(result, Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_compute_fold_histogram() {
+        let result = compute_fold(
+            0..1000,
+            HashMap::new,
+            |mut counts, x: u32| {
+                *counts.entry(x % 7).or_insert(0) += 1;
+                counts
+            },
+            |mut counts, other| {
+                for (key, count) in other {
+                    *counts.entry(key).or_insert(0) += count;
+                }
+                counts
+            },
+        );
+
+        let mut expected = HashMap::new();
+        for x in 0..1000u32 {
+            *expected.entry(x % 7).or_insert(0) += 1;
+        }
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_compute_fold_preserves_order() {
+        let result = compute_fold(
+            0..1000,
+            Vec::new,
+            |mut bucket, x: i32| {
+                if x % 3 == 0 {
+                    bucket.push(x);
+                }
+                bucket
+            },
+            |mut bucket, other| {
+                bucket.extend(other);
+                bucket
+            },
+        );
+        assert_eq!(result, (0..1000).filter(|x| x % 3 == 0).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,178 @@
+use std::mem;
+use std::sync::Arc;
+
+use crate::config::map_chunks;
+use crate::Compute;
+
+/// Computes the inclusive prefix scan of `input` with the associative operation `op` in parallel: the `i`-th output
+/// is `op` applied to the elements `0..=i`.
+///
+/// `identity` must be a neutral element for `op` (`op(&identity, &x) == x`). `op` doesn't need to be commutative.
+///
+/// The scan runs in two parallel passes: each thread first scans its own chunk, then the totals of the chunks are
+/// scanned sequentially, and finally each thread combines its chunk with the total of all the chunks before it.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_scan;
+/// let output = compute_scan(vec![1, 2, 3, 4, 5, 6], 0, |a, b| a + b);
+/// assert_eq!(output, vec![1, 3, 6, 10, 15, 21]);
+/// ```
+pub fn compute_scan<T, F>(input: impl IntoIterator<Item = T>, identity: T, op: F) -> Vec<T>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T, &T) -> T + Send + Sync + 'static,
+{
+    scan(input, identity, op, Scan::Inclusive)
+}
+
+/// Computes the exclusive prefix scan of `input` with the associative operation `op` in parallel: the `i`-th output
+/// is `op` applied to the elements `0..i`, so the first output is `identity`.
+///
+/// This is typically used to compute the offsets of variable-length records from their lengths. See
+/// [`compute_scan`] for the requirements on `identity` and `op`.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_scan_exclusive;
+/// let lengths = vec![3, 1, 4, 1, 5, 9];
+/// let offsets = compute_scan_exclusive(lengths, 0, |a, b| a + b);
+/// assert_eq!(offsets, vec![0, 3, 4, 8, 9, 14]);
+/// ```
+pub fn compute_scan_exclusive<T, F>(
+    input: impl IntoIterator<Item = T>,
+    identity: T,
+    op: F,
+) -> Vec<T>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T, &T) -> T + Send + Sync + 'static,
+{
+    scan(input, identity, op, Scan::Exclusive)
+}
+
+/// Whether the element at a given index is included in its own output.
+#[derive(Clone, Copy)]
+enum Scan {
+    Inclusive,
+    Exclusive,
+}
+
+fn scan<T, F>(input: impl IntoIterator<Item = T>, identity: T, op: F, kind: Scan) -> Vec<T>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T, &T) -> T + Send + Sync + 'static,
+{
+    let op = Arc::new(op);
+
+    // First pass: scan every chunk on its own, keeping track of its total
+    let scanned_chunks = {
+        let identity = identity.clone();
+        let op = Arc::clone(&op);
+
+        map_chunks(input, move |_, chunk| {
+            let mut scanned = Vec::with_capacity(chunk.len());
+            let mut total = identity.clone();
+
+            for item in chunk {
+                let next = op(&total, &item);
+
+                match kind {
+                    Scan::Inclusive => {
+                        scanned.push(next.clone());
+                        total = next;
+                    }
+                    Scan::Exclusive => scanned.push(mem::replace(&mut total, next)),
+                }
+            }
+
+            (scanned, total)
+        })
+    };
+
+    // A single chunk needs no offset, which is always the case for small inputs
+    if scanned_chunks.len() == 1 {
+        return scanned_chunks.into_iter().next().unwrap().0;
+    }
+
+    // Scan the totals of the chunks to get the offset of every chunk, there are only a few of them
+    let mut offset = identity;
+    let mut offset_chunks = Vec::with_capacity(scanned_chunks.len());
+    for (scanned, total) in scanned_chunks {
+        let next_offset = op(&offset, &total);
+        offset_chunks.push((mem::replace(&mut offset, next_offset), scanned));
+    }
+
+    // Second pass: combine every chunk with its offset. Each chunk is an element of the input here, and must be
+    // processed in parallel even though there are only a few of them.
+    let outputs = Compute::new()
+        .sequential_threshold(0)
+        .chunk_size(1)
+        .map_chunks(offset_chunks, move |_, offset_chunks| {
+            offset_chunks
+                .into_iter()
+                .flat_map(|(offset, scanned)| {
+                    scanned
+                        .into_iter()
+                        .map(|item| op(&offset, &item))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        })
+        .expect("the scan configuration is valid");
+
+    outputs.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{compute_scan, compute_scan_exclusive};
+
+    #[test]
+    fn test_compute_scan_empty_input() {
+        assert_eq!(compute_scan(Vec::<i32>::new(), 0, |a, b| a + b), vec![]);
+        assert_eq!(
+            compute_scan_exclusive(Vec::<i32>::new(), 0, |a, b| a + b),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_compute_scan_prefix_sums() {
+        let expected: Vec<u64> = (1..=1000)
+            .scan(0, |total, x| {
+                *total += x;
+                Some(*total)
+            })
+            .collect();
+        assert_eq!(compute_scan(1..=1000u64, 0, |a, b| a + b), expected);
+    }
+
+    #[test]
+    fn test_compute_scan_exclusive_prefix_sums() {
+        let expected: Vec<u64> = (1..=1000)
+            .scan(0, |total, x| {
+                let previous = *total;
+                *total += x;
+                Some(previous)
+            })
+            .collect();
+        assert_eq!(
+            compute_scan_exclusive(1..=1000u64, 0, |a, b| a + b),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_compute_scan_non_commutative() {
+        let input: Vec<String> = (0..100).map(|x| (x % 10).to_string()).collect();
+        let result = compute_scan(input.clone(), String::new(), |a, b| format!("{a}{b}"));
+
+        assert_eq!(result.len(), 100);
+        for (index, prefix) in result.iter().enumerate() {
+            assert_eq!(*prefix, input[..=index].concat());
+        }
+    }
+}
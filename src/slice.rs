@@ -0,0 +1,184 @@
+use std::panic::resume_unwind;
+use std::thread::scope;
+
+use crate::{split_into_chunks, Compute, Split};
+
+/// Computes the given function `f` on each element of the borrowed slice `input` in parallel using scoped threads.
+///
+/// Unlike [`compute`](crate::compute), the input is neither copied nor required to be `'static`, so data owned by
+/// the caller's stack frame can be processed directly. The function receives a reference to each element.
+///
+/// If the input is small enough (less than the `THRESHOLD` constant), the computation is
+/// performed in the calling thread instead of spawning new threads. A panic in `f` is propagated to the caller.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_slice;
+/// let words = vec![String::from("a"), String::from("bb"), String::from("ccc")];
+/// let lengths = compute_slice(&words, |word| word.len());
+/// assert_eq!(lengths, vec![1, 2, 3]);
+/// ```
+pub fn compute_slice<'a, T, R, F>(input: &'a [T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&'a T) -> R + Sync,
+{
+    let f = &f;
+
+    map_split_scoped(input, |chunk: &'a [T]| {
+        chunk.iter().map(f).collect::<Vec<_>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Applies the given function `f` to each element of the mutable slice `input` in parallel using scoped threads.
+///
+/// The slice is split into disjoint chunks, each updated in place by its own thread, so nothing is reallocated.
+///
+/// If the input is small enough (less than the `THRESHOLD` constant), the computation is
+/// performed in the calling thread instead of spawning new threads. A panic in `f` is propagated to the caller.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_in_place;
+/// let mut scores = vec![10.0, 20.0, 40.0, 80.0, 160.0, 320.0];
+/// compute_in_place(&mut scores, |score| *score *= 0.5);
+/// assert_eq!(scores, vec![5.0, 10.0, 20.0, 40.0, 80.0, 160.0]);
+/// ```
+pub fn compute_in_place<T, F>(input: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(&mut T) + Sync,
+{
+    let f = &f;
+
+    map_split_scoped(input, |chunk: &mut [T]| chunk.iter_mut().for_each(f));
+}
+
+/// Splits `input` into chunks and runs `job` on each of them in parallel on scoped threads, returning the outputs in
+/// chunk order.
+pub(crate) fn map_split_scoped<S, X, J>(input: S, job: J) -> Vec<X>
+where
+    S: Split + Send,
+    X: Send,
+    J: Fn(S) -> X + Sync,
+{
+    let plan = Compute::new()
+        .plan(input.len())
+        .expect("the default configuration is valid");
+
+    // If the input is small enough, just compute it in the calling thread
+    let Some(plan) = plan else {
+        return vec![job(input)];
+    };
+    let job = &job;
+
+    scope(|scope| {
+        let thread_handles = split_into_chunks(input, plan.chunk_size)
+            .into_iter()
+            .map(|chunk| scope.spawn(move || job(chunk)))
+            .collect::<Vec<_>>();
+
+        thread_handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| resume_unwind(payload))
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{compute_in_place, compute_slice};
+
+    #[test]
+    fn test_compute_slice_empty_input() {
+        let input: Vec<i32> = vec![];
+        let result = compute_slice(&input, |x| x * 2);
+        assert_eq!(result, vec![]);
+    }
+
+    #[test]
+    fn test_compute_slice_large_input() {
+        let input = vec![1; 1000];
+        let result = compute_slice(&input, |x| x * 2);
+        assert_eq!(result, vec![2; 1000]);
+    }
+
+    #[test]
+    fn test_compute_slice_borrows_non_clone_data() {
+        struct Record {
+            value: usize,
+        }
+
+        let factor = 3;
+        let input: Vec<Record> = (0..100).map(|value| Record { value }).collect();
+        let result = compute_slice(&input, |record| record.value * factor);
+        assert_eq!(result, (0..100).map(|x| x * 3).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_compute_slice_returns_references() {
+        let input: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+        let result = compute_slice(&input, |s| s.as_str());
+        assert_eq!(result, input.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "bad element 500")]
+    fn test_compute_slice_propagates_panic() {
+        let input: Vec<usize> = (0..1000).collect();
+        compute_slice(&input, |x| {
+            if *x == 500 {
+                panic!("bad element {x}");
+            }
+            x * 2
+        });
+    }
+
+    #[test]
+    fn test_compute_in_place_empty_input() {
+        let mut input: Vec<i32> = vec![];
+        compute_in_place(&mut input, |x| *x *= 2);
+        assert_eq!(input, vec![]);
+    }
+
+    #[test]
+    fn test_compute_in_place_large_input() {
+        let mut input: Vec<u64> = (0..1000).collect();
+        let address = input.as_ptr();
+        compute_in_place(&mut input, |x| *x = *x * *x);
+        assert_eq!(input, (0..1000).map(|x| x * x).collect::<Vec<_>>());
+        assert_eq!(input.as_ptr(), address);
+    }
+
+    #[test]
+    fn test_compute_in_place_sub_slice() {
+        let decay = 2;
+        let mut input = [100; 20];
+        compute_in_place(&mut input[5..15], |x| *x /= decay);
+        assert_eq!(input[..5], [100; 5]);
+        assert_eq!(input[5..15], [50; 10]);
+        assert_eq!(input[15..], [100; 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bad element 500")]
+    fn test_compute_in_place_propagates_panic() {
+        let mut input: Vec<usize> = (0..1000).collect();
+        compute_in_place(&mut input, |x| {
+            if *x == 500 {
+                panic!("bad element {x}");
+            }
+            *x *= 2;
+        });
+    }
+}
@@ -0,0 +1,298 @@
+use std::cmp::Ordering;
+use std::panic::resume_unwind;
+use std::thread::scope;
+
+use crate::Compute;
+
+/// Sorts the slice `input` in parallel, like [`slice::sort`].
+///
+/// The sort is stable: equal elements keep their relative order, and the result is always identical to the one of
+/// [`slice::sort`].
+///
+/// The slice is split into chunks which are sorted by their own thread, then neighbouring sorted chunks are merged
+/// pairwise, in parallel, until a single sorted run remains. Every merge is itself split into smaller independent
+/// merges computed in parallel, so the last passes keep all the threads busy too. If the input is small enough (less
+/// than the `THRESHOLD` constant), it is sorted in the calling thread instead.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::par_sort;
+/// let mut input = vec![5, 3, 9, 1, 7, 2, 8, 6, 4, 0];
+/// par_sort(&mut input);
+/// assert_eq!(input, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+/// ```
+pub fn par_sort<T>(input: &mut [T])
+where
+    T: Ord + Send,
+{
+    par_sort_with(Compute::new(), input, <[T]>::sort, T::lt);
+}
+
+/// Sorts the slice `input` in parallel, like [`slice::sort_unstable`].
+///
+/// The sort is not stable: equal elements may be reordered. The chunks are sorted with [`slice::sort_unstable`],
+/// which doesn't allocate, but the smallest merges still use [`slice::sort_by`], which allocates a buffer, like
+/// [`par_sort`] does.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::par_sort_unstable;
+/// let mut input = vec![5, 3, 9, 1, 7, 2, 8, 6, 4, 0];
+/// par_sort_unstable(&mut input);
+/// assert_eq!(input, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+/// ```
+pub fn par_sort_unstable<T>(input: &mut [T])
+where
+    T: Ord + Send,
+{
+    par_sort_with(Compute::new(), input, <[T]>::sort_unstable, T::lt);
+}
+
+/// Sorts the slice `input` in parallel with the key extracted by `f`, like [`slice::sort_by_key`].
+///
+/// The sort is stable: elements with equal keys keep their relative order, and the result is always identical to the
+/// one of [`slice::sort_by_key`]. The key is extracted again for every comparison, on each merge pass.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::par_sort_by_key;
+/// let mut input = vec![(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd'), (1, 'e'), (0, 'f')];
+/// par_sort_by_key(&mut input, |(key, _)| *key);
+/// assert_eq!(input, vec![(0, 'd'), (0, 'f'), (1, 'b'), (1, 'e'), (2, 'a'), (2, 'c')]);
+/// ```
+pub fn par_sort_by_key<T, K, F>(input: &mut [T], f: F)
+where
+    T: Send,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    let f = &f;
+
+    par_sort_with(
+        Compute::new(),
+        input,
+        |chunk| chunk.sort_by_key(f),
+        |a, b| f(a) < f(b),
+    );
+}
+
+/// Sorts every chunk of `input`, split according to `compute`, in parallel with `sort`, then merges the sorted runs
+/// pairwise in parallel, doubling the size of the runs at each pass.
+///
+/// `is_less` must be consistent with the order `sort` sorts the chunks in.
+fn par_sort_with<T, S, L>(compute: Compute<'_>, input: &mut [T], sort: S, is_less: L)
+where
+    T: Send,
+    S: Fn(&mut [T]) + Sync,
+    L: Fn(&T, &T) -> bool + Sync,
+{
+    let plan = compute
+        .plan(input.len())
+        .expect("the sort configuration is valid");
+
+    // If the input is small enough, just sort it in the calling thread
+    let Some(plan) = plan else {
+        sort(input);
+        return;
+    };
+    let (sort, is_less) = (&sort, &is_less);
+
+    scope(|scope| {
+        for chunk in input.chunks_mut(plan.chunk_size) {
+            scope.spawn(move || sort(chunk));
+        }
+    });
+
+    let mut run_size = plan.chunk_size;
+    while run_size < input.len() {
+        scope(|scope| {
+            // The last run has no neighbour to be merged with when the number of runs is odd
+            for runs in input
+                .chunks_mut(run_size * 2)
+                .filter(|runs| runs.len() > run_size)
+            {
+                scope.spawn(move || par_merge(runs, run_size, is_less, plan.chunk_size));
+            }
+        });
+
+        run_size *= 2;
+    }
+}
+
+/// Merges the neighbouring sorted runs `runs[..mid]` and `runs[mid..]` in place, keeping equal elements in order.
+///
+/// The longer run is cut in its middle, and the other one where its middle element belongs. Rotating the elements
+/// between the two cuts leaves two smaller merges which are independent of each other, so they are computed in
+/// parallel, until they have no more than `sequential_size` elements and are merged with [`slice::sort_by`].
+fn par_merge<T, L>(runs: &mut [T], mid: usize, is_less: &L, sequential_size: usize)
+where
+    T: Send,
+    L: Fn(&T, &T) -> bool + Sync,
+{
+    if mid == 0 || mid == runs.len() {
+        return;
+    }
+
+    // Two runs of a single element can't be cut any further
+    if runs.len() <= sequential_size.max(2) {
+        runs.sort_by(|a, b| {
+            if is_less(a, b) {
+                Ordering::Less
+            } else if is_less(b, a) {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        });
+        return;
+    }
+
+    // The elements of the second run equal to the middle element must stay after it, and the ones of the first run
+    // before it.
+    let (left_mid, split) = if mid >= runs.len() - mid {
+        let first_cut = mid / 2;
+        let second_cut = runs[mid..].partition_point(|x| is_less(x, &runs[first_cut]));
+        runs[first_cut..mid + second_cut].rotate_left(mid - first_cut);
+        (first_cut, first_cut + second_cut)
+    } else {
+        let second_cut = mid + (runs.len() - mid) / 2;
+        let first_cut = runs[..mid].partition_point(|x| !is_less(&runs[second_cut], x));
+        runs[first_cut..second_cut].rotate_left(mid - first_cut);
+        (first_cut, first_cut + second_cut - mid)
+    };
+
+    let (left, right) = runs.split_at_mut(split);
+    scope(|scope| {
+        let handle = scope.spawn(|| par_merge(left, left_mid, is_less, sequential_size));
+        par_merge(right, mid - left_mid, is_less, sequential_size);
+        handle
+            .join()
+            .unwrap_or_else(|payload| resume_unwind(payload));
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sort::{par_merge, par_sort_with};
+    use crate::{par_sort, par_sort_by_key, par_sort_unstable, Compute};
+
+    /// Returns `size` pseudo-random numbers below `modulo`, so the tests are deterministic.
+    fn pseudo_random(size: usize, modulo: u64) -> Vec<u64> {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+
+        (0..size)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                (state >> 33) % modulo
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_par_sort_matches_slice_sort() {
+        for size in [0, 1, 4, 5, 17, 100, 1000, 4099] {
+            let mut input = pseudo_random(size, 1000);
+            let mut expected = input.clone();
+            expected.sort();
+
+            par_sort(&mut input);
+            assert_eq!(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_par_sort_unstable_matches_slice_sort() {
+        for size in [0, 1, 4, 5, 17, 100, 1000, 4099] {
+            let mut input = pseudo_random(size, 50);
+            let mut expected = input.clone();
+            expected.sort_unstable();
+
+            par_sort_unstable(&mut input);
+            assert_eq!(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_par_sort_is_stable() {
+        let mut input: Vec<(u64, usize)> = pseudo_random(5000, 10).into_iter().zip(0..).collect();
+        let mut expected = input.clone();
+        expected.sort_by_key(|(key, _)| *key);
+
+        par_sort_by_key(&mut input, |(key, _)| *key);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn test_par_sort_already_sorted_and_reversed() {
+        let mut input: Vec<u32> = (0..1000).collect();
+        par_sort(&mut input);
+        assert_eq!(input, (0..1000).collect::<Vec<_>>());
+
+        let mut input: Vec<u32> = (0..1000).rev().collect();
+        par_sort(&mut input);
+        assert_eq!(input, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_par_sort_merges_many_runs() {
+        for chunk_size in [1, 2, 3, 7, 64] {
+            let mut input: Vec<(u64, usize)> =
+                pseudo_random(1000, 20).into_iter().zip(0..).collect();
+            let mut expected = input.clone();
+            expected.sort_by_key(|(key, _)| *key);
+
+            let compute = Compute::new()
+                .sequential_threshold(0)
+                .chunk_size(chunk_size);
+            par_sort_with(
+                compute,
+                &mut input,
+                |chunk| chunk.sort_by_key(|(key, _)| *key),
+                |a, b| a.0 < b.0,
+            );
+            assert_eq!(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_par_merge_uneven_runs() {
+        for (first_size, second_size) in [
+            (0, 10),
+            (10, 0),
+            (1, 1),
+            (1, 500),
+            (500, 1),
+            (300, 700),
+            (999, 998),
+        ] {
+            let mut first: Vec<(u64, usize)> =
+                pseudo_random(first_size, 20).into_iter().zip(0..).collect();
+            let mut second: Vec<(u64, usize)> = pseudo_random(second_size, 20)
+                .into_iter()
+                .zip(first_size..)
+                .collect();
+            first.sort();
+            second.sort();
+
+            let input = [first, second].concat();
+            let mut expected = input.clone();
+            expected.sort_by_key(|(key, _)| *key);
+
+            for sequential_size in [1, 2, 16, 4096] {
+                let mut runs = input.clone();
+                par_merge(
+                    &mut runs,
+                    first_size,
+                    &|a: &(u64, usize), b: &(u64, usize)| a.0 < b.0,
+                    sequential_size,
+                );
+                assert_eq!(runs, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+use crate::config::spawn_each;
+use crate::pool::is_worker_thread;
+use crate::ThreadPool;
+
+/// Computes the given function `f` on each element of `input` in parallel, returning an iterator which yields every
+/// result with the index of its element as soon as it is computed, in completion order.
+///
+/// The computation is dispatched onto the pool before returning and keeps running in the background while the results
+/// are consumed. Dropping the iterator makes the threads stop computing the remaining elements. A panic in `f` is
+/// propagated to the caller when the iterator reaches it. Use [`compute_stream_ordered`] to get the results in input
+/// order.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_stream;
+/// let mut output: Vec<(usize, i32)> = compute_stream(vec![1, 2, 3, 4, 5, 6], |t| t * 2).collect();
+/// output.sort();
+/// assert_eq!(output, vec![(0, 2), (1, 4), (2, 6), (3, 8), (4, 10), (5, 12)]);
+/// ```
+pub fn compute_stream<T, R, F>(input: impl IntoIterator<Item = T>, f: F) -> Stream<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let receiver = spawn_each(input.into_iter().collect(), f, || false);
+
+    Stream { receiver }
+}
+
+/// Computes the given function `f` on each element of `input` in parallel, returning an iterator which yields the
+/// results in input order as soon as they and all the results before them are computed.
+///
+/// Results computed ahead of their turn are buffered until all the results before them are yielded. See
+/// [`compute_stream`] for the behavior of the background computation.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_stream_ordered;
+/// let output: Vec<i32> = compute_stream_ordered(vec![1, 2, 3, 4, 5, 6], |t| t * 2).collect();
+/// assert_eq!(output, vec![2, 4, 6, 8, 10, 12]);
+/// ```
+pub fn compute_stream_ordered<T, R, F>(input: impl IntoIterator<Item = T>, f: F) -> OrderedStream<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    OrderedStream {
+        stream: compute_stream(input, f),
+        next_index: 0,
+        buffer: HashMap::new(),
+    }
+}
+
+/// Computes the given function `f` on each element pulled from `input` in parallel, passing the results to `sink` in
+/// input order, while keeping at most `max_in_flight` elements in memory.
+///
+/// Unlike the other computations, the input is never collected: elements are pulled in batches only when there is
+/// room for them, so `input` may be larger than the available memory, or even infinite. An element is in flight from
+/// the moment it is pulled until its result is passed to `sink`, which runs in the calling thread. A panic in `f` is
+/// propagated to the caller.
+///
+/// # Panics
+///
+/// Panics if `max_in_flight` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use simple_parallel_compute::compute_bounded;
+/// let lines = (1..=1000).map(|i| format!("line {i}"));
+/// let mut total_length = 0;
+/// compute_bounded(lines, 64, |line| line.len(), |length| total_length += length);
+/// assert_eq!(total_length, (1..=1000).map(|i| format!("line {i}").len()).sum::<usize>());
+/// ```
+pub fn compute_bounded<T, R, F, S>(
+    input: impl IntoIterator<Item = T>,
+    max_in_flight: usize,
+    f: F,
+    mut sink: S,
+) where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+    S: FnMut(R),
+{
+    assert!(max_in_flight > 0, "at least one element must be in flight");
+
+    let mut input = input.into_iter().fuse();
+
+    // Waiting for other jobs from inside a worker could leave the pool with no thread to run them
+    if is_worker_thread() {
+        input.map(f).for_each(sink);
+        return;
+    }
+
+    let pool = ThreadPool::global();
+
+    // Several batches per thread fit in flight at the same time, so the threads are kept busy while the oldest batch
+    // is waited for.
+    let batch_size = (max_in_flight / (pool.threads() * 2)).max(1);
+
+    let f = Arc::new(f);
+    let (sender, receiver) = channel();
+
+    let mut in_flight = 0;
+    let mut dispatched_batches = 0;
+    let mut sunk_batches = 0;
+    let mut ready_batches = HashMap::new();
+    let mut exhausted = false;
+
+    loop {
+        while !exhausted && in_flight + batch_size <= max_in_flight {
+            let batch: Vec<T> = input.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                exhausted = true;
+                break;
+            }
+
+            in_flight += batch.len();
+
+            let f = Arc::clone(&f);
+            let sender = sender.clone();
+            let index = dispatched_batches;
+            pool.execute(move || {
+                let results = catch_unwind(AssertUnwindSafe(|| {
+                    batch.into_iter().map(&*f).collect::<Vec<_>>()
+                }));
+                let _ = sender.send(results.map(|results| (index, results)));
+            });
+
+            dispatched_batches += 1;
+        }
+
+        if exhausted && sunk_batches == dispatched_batches {
+            break;
+        }
+
+        // There is always a batch in flight here, and every batch sends its results, so this never blocks forever
+        let (index, results) = receiver
+            .recv()
+            .expect("a sender is kept alive")
+            .unwrap_or_else(|payload| resume_unwind(payload));
+        ready_batches.insert(index, results);
+
+        while let Some(results) = ready_batches.remove(&sunk_batches) {
+            in_flight -= results.len();
+            results.into_iter().for_each(&mut sink);
+            sunk_batches += 1;
+        }
+    }
+}
+
+/// An iterator over the results of [`compute_stream`], with the index of their element, in completion order.
+pub struct Stream<R> {
+    receiver: Receiver<thread::Result<(usize, R)>>,
+}
+
+impl<R> Iterator for Stream<R> {
+    type Item = (usize, R);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The channel is closed once every chunk is computed
+        match self.receiver.recv().ok()? {
+            Ok(result) => Some(result),
+            Err(payload) => resume_unwind(payload),
+        }
+    }
+}
+
+/// An iterator over the results of [`compute_stream_ordered`], in input order.
+pub struct OrderedStream<R> {
+    stream: Stream<R>,
+    next_index: usize,
+    buffer: HashMap<usize, R>,
+}
+
+impl<R> Iterator for OrderedStream<R> {
+    type Item = R;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = match self.buffer.remove(&self.next_index) {
+            Some(result) => result,
+            None => loop {
+                let (index, result) = self.stream.next()?;
+
+                if index == self.next_index {
+                    break result;
+                }
+
+                self.buffer.insert(index, result);
+            },
+        };
+
+        self.next_index += 1;
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use crate::{compute_bounded, compute_stream, compute_stream_ordered, ThreadPool};
+
+    #[test]
+    fn test_compute_stream_empty_input() {
+        assert_eq!(compute_stream(Vec::<i32>::new(), |x| x).count(), 0);
+        assert_eq!(compute_stream_ordered(Vec::<i32>::new(), |x| x).count(), 0);
+    }
+
+    #[test]
+    fn test_compute_stream_yields_every_result() {
+        let mut result: Vec<(usize, u64)> = compute_stream(0..1000u64, |x| x * 2).collect();
+        result.sort();
+        assert_eq!(
+            result,
+            (0..1000u64)
+                .map(|x| (x as usize, x * 2))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_compute_stream_ordered_preserves_order() {
+        let result: Vec<u64> = compute_stream_ordered(0..1000u64, |x| {
+            if x % 100 == 0 {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            x * 2
+        })
+        .collect();
+        assert_eq!(result, (0..1000).map(|x| x * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_compute_stream_yields_before_completion() {
+        let gate = Arc::new(AtomicBool::new(false));
+        let opened = Arc::clone(&gate);
+
+        let mut stream = compute_stream_ordered(0..16, move |x| {
+            while x > 0 && !opened.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            x
+        });
+
+        // The other elements are blocked until the gate is opened, so the first result must arrive on its own
+        assert_eq!(stream.next(), Some(0));
+        gate.store(true, Ordering::SeqCst);
+        assert_eq!(stream.collect::<Vec<_>>(), (1..16).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_compute_stream_small_input_runs_in_background() {
+        let start = Instant::now();
+        let stream = compute_stream(vec![1, 2], |x| {
+            std::thread::sleep(Duration::from_millis(500));
+            x
+        });
+        assert!(start.elapsed() < Duration::from_millis(250));
+        assert_eq!(stream.count(), 2);
+    }
+
+    #[test]
+    fn test_compute_stream_from_pool_job_runs_in_background() {
+        let pool = ThreadPool::new(1);
+        let (sender, receiver) = channel();
+
+        pool.execute(move || {
+            let start = Instant::now();
+            let stream = compute_stream(0..16, |x| {
+                std::thread::sleep(Duration::from_millis(50));
+                x
+            });
+            let elapsed = start.elapsed();
+            sender.send((elapsed, stream.count())).unwrap();
+        });
+
+        let (elapsed, count) = receiver.recv().unwrap();
+        assert!(elapsed < Duration::from_millis(400));
+        assert_eq!(count, 16);
+    }
+
+    #[test]
+    #[should_panic(expected = "stream failure")]
+    fn test_compute_stream_propagates_panic() {
+        compute_stream(0..100, |x| {
+            if x == 42 {
+                panic!("stream failure");
+            }
+            x
+        })
+        .for_each(drop);
+    }
+
+    #[test]
+    fn test_compute_bounded_preserves_order() {
+        let mut result = Vec::new();
+        compute_bounded(0..10_000u64, 100, |x| x * 2, |x| result.push(x));
+        assert_eq!(result, (0..10_000).map(|x| x * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_compute_bounded_limits_elements_in_flight() {
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&pulled);
+        let input = (0..).take(5000).inspect(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let mut sunk = 0;
+        let mut max_in_flight = 0;
+        compute_bounded(
+            input,
+            32,
+            |x: usize| x + 1,
+            |x| {
+                // The result of the element is computed, but it is still in flight until this call
+                max_in_flight = max_in_flight.max(pulled.load(Ordering::SeqCst) - sunk);
+                sunk += 1;
+                assert_eq!(x, sunk);
+            },
+        );
+
+        assert_eq!(sunk, 5000);
+        assert!(max_in_flight <= 32);
+    }
+
+    #[test]
+    fn test_compute_bounded_single_element_in_flight() {
+        let mut result = Vec::new();
+        compute_bounded(vec!["a", "b", "c"], 1, str::to_uppercase, |x| {
+            result.push(x)
+        });
+        assert_eq!(result, vec!["A", "B", "C"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bounded failure")]
+    fn test_compute_bounded_propagates_panic() {
+        compute_bounded(
+            0..100,
+            10,
+            |x| {
+                if x == 42 {
+                    panic!("bounded failure");
+                }
+                x
+            },
+            drop,
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt::{self, Display, Formatter};
+use std::panic::resume_unwind;
+use std::time::{Duration, Instant};
+
+use crate::config::spawn_each;
+
+/// The outcome of a computation started with [`compute_with_timeout`] which did not finish before its deadline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimedOut<R> {
+    completed: Vec<(usize, R)>,
+    unfinished: Vec<usize>,
+}
+
+impl<R> TimedOut<R> {
+    /// Returns the results computed before the deadline, with the index of their element, in input order.
+    pub fn completed(&self) -> &[(usize, R)] {
+        &self.completed
+    }
+
+    /// Returns the indices of the elements which were not computed before the deadline, in input order.
+    pub fn unfinished(&self) -> &[usize] {
+        &self.unfinished
+    }
+
+    /// Returns the results computed before the deadline, with the index of their element, in input order.
+    pub fn into_completed(self) -> Vec<(usize, R)> {
+        self.completed
+    }
+}
+
+impl<R> Display for TimedOut<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the computation timed out with {} of {} elements unfinished",
+            self.unfinished.len(),
+            self.completed.len() + self.unfinished.len()
+        )
+    }
+}
+
+impl<R: fmt::Debug> std::error::Error for TimedOut<R> {}
+
+/// Computes the given function `f` on each element of `input` in parallel, like [`compute`](crate::compute), giving
+/// up once `timeout` has elapsed.
+///
+/// The computation is dispatched onto the pool, so `f` never runs in the calling thread. The threads stop picking up
+/// new elements as soon as the deadline passes, and the call returns right away without waiting for the elements being
+/// computed at that time, which keep running in the background and whose results are discarded. A panic in `f` before
+/// the deadline is propagated to the caller.
+///
+/// # Errors
+///
+/// Returns [`TimedOut`] with the results computed so far and the indices of the other elements if the deadline passed
+/// before all the elements were computed.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use simple_parallel_compute::compute_with_timeout;
+/// let output = compute_with_timeout(vec![1, 2, 3, 4, 5, 6], |t| t * 2, Duration::from_secs(10));
+/// assert_eq!(output, Ok(vec![2, 4, 6, 8, 10, 12]));
+///
+/// let output = compute_with_timeout(vec![1, 2, 3, 4, 5, 6], |t| t * 2, Duration::ZERO);
+/// assert_eq!(output.unwrap_err().unfinished(), &[0, 1, 2, 3, 4, 5]);
+/// ```
+pub fn compute_with_timeout<T, R, F>(
+    input: impl IntoIterator<Item = T>,
+    f: F,
+    timeout: Duration,
+) -> Result<Vec<R>, TimedOut<R>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    // A timeout too large to be represented never passes
+    let deadline = Instant::now().checked_add(timeout);
+    let expired = move || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+    let input: Vec<T> = input.into_iter().collect();
+    let len = input.len();
+
+    // The threads stop picking up new elements once the caller gave up
+    let receiver = spawn_each(input, f, expired);
+
+    let mut results: Vec<Option<R>> = (0..len).map(|_| None).collect();
+    let mut remaining = len;
+
+    while remaining > 0 {
+        let message = match deadline {
+            Some(deadline) => receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .ok(),
+            None => receiver.recv().ok(),
+        };
+
+        // Either the deadline passed, or all the threads stopped because of it
+        let Some(message) = message else { break };
+
+        let (index, result) = message.unwrap_or_else(|payload| resume_unwind(payload));
+        results[index] = Some(result);
+        remaining -= 1;
+    }
+
+    if remaining == 0 {
+        return Ok(results.into_iter().flatten().collect());
+    }
+
+    let mut completed = Vec::with_capacity(len - remaining);
+    let mut unfinished = Vec::with_capacity(remaining);
+    for (index, result) in results.into_iter().enumerate() {
+        match result {
+            Some(result) => completed.push((index, result)),
+            None => unfinished.push(index),
+        }
+    }
+
+    Err(TimedOut {
+        completed,
+        unfinished,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::{compute_with_timeout, ThreadPool};
+
+    #[test]
+    fn test_compute_with_timeout_completes() {
+        let result = compute_with_timeout(0..1000, |x| x * 2, Duration::from_secs(60));
+        assert_eq!(result, Ok((0..1000).map(|x| x * 2).collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn test_compute_with_timeout_empty_input() {
+        let input: Vec<i32> = vec![];
+        assert_eq!(
+            compute_with_timeout(input, |x| x, Duration::ZERO),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn test_compute_with_timeout_max_duration() {
+        let result = compute_with_timeout(0..10, |x| x + 1, Duration::MAX);
+        assert_eq!(result, Ok((1..11).collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn test_compute_with_timeout_small_input_expires() {
+        let start = Instant::now();
+        let result = compute_with_timeout(
+            vec![1, 2],
+            |x| {
+                thread::sleep(Duration::from_millis(500));
+                x
+            },
+            Duration::from_millis(10),
+        );
+        assert!(start.elapsed() < Duration::from_millis(250));
+        assert_eq!(result.unwrap_err().unfinished(), &[0, 1]);
+    }
+
+    #[test]
+    fn test_compute_with_timeout_from_pool_job_expires() {
+        let pool = ThreadPool::new(1);
+        let (sender, receiver) = channel();
+
+        pool.execute(move || {
+            let start = Instant::now();
+            let result = compute_with_timeout(
+                0..16,
+                |x| {
+                    thread::sleep(Duration::from_millis(500));
+                    x
+                },
+                Duration::from_millis(10),
+            );
+            sender.send((start.elapsed(), result.is_err())).unwrap();
+        });
+
+        let (elapsed, timed_out) = receiver.recv().unwrap();
+        assert!(elapsed < Duration::from_millis(250));
+        assert!(timed_out);
+    }
+
+    #[test]
+    fn test_compute_with_timeout_expires() {
+        let gate = Arc::new(AtomicBool::new(false));
+        let opened = Arc::clone(&gate);
+
+        // The odd elements are blocked until the gate is opened after the call, so they can never finish in time
+        let start = Instant::now();
+        let result = compute_with_timeout(
+            0..100,
+            move |x| {
+                while x % 2 == 1 && !opened.load(Ordering::SeqCst) {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                x * 2
+            },
+            Duration::from_millis(200),
+        );
+        assert!(start.elapsed() < Duration::from_secs(2));
+        gate.store(true, Ordering::SeqCst);
+
+        let timed_out = result.unwrap_err();
+        assert!((1..100)
+            .step_by(2)
+            .all(|index| timed_out.unfinished().contains(&index)));
+        assert_eq!(
+            timed_out.completed().len() + timed_out.unfinished().len(),
+            100
+        );
+        for &(index, result) in timed_out.completed() {
+            assert_eq!(result, index * 2);
+            assert!(!timed_out.unfinished().contains(&index));
+        }
+    }
+}